@@ -0,0 +1,159 @@
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, LitStr};
+
+// Implements `crate::gl::Vertex` (and `crate::gl::Pod`) for a `#[repr(C)]` struct.
+//
+// Every field becomes one vertex attribute. The component type and count come
+// from the field type's `VertexComponent` impl. Field attributes:
+//
+// - `#[vertex(name = "textureCoord")]` sets the shader attribute name.
+//   Defaults to the field name in lowerCamelCase.
+// - `#[vertex(normalized)]` maps integer components to [0, 1] or [-1, 1].
+// - `#[vertex(integer)]` keeps integer components as integers (ivec/uvec inputs).
+//
+// The generated code names the traits as `crate::gl::...`, and `gl` is a
+// private module of the app crate, so the derive only works inside that crate.
+#[proc_macro_derive(Vertex, attributes(vertex))]
+pub fn derive_vertex(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+struct Field {
+    ident: syn::Ident,
+    ty: syn::Type,
+    name: String,
+    normalized: bool,
+    integer: bool,
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream2, Error> {
+    let ident = &input.ident;
+
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(&input.generics, "Vertex cannot be derived for generic structs"));
+    }
+    if !has_repr_c(&input) {
+        return Err(Error::new(Span::call_site(), "Vertex requires #[repr(C)]"));
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => fields.named.iter().map(parse_field).collect::<Result<Vec<_>, _>>()?,
+            _ => return Err(Error::new_spanned(&data.fields, "Vertex requires named fields")),
+        },
+        _ => return Err(Error::new(Span::call_site(), "Vertex can only be derived for structs")),
+    };
+
+    let types = fields.iter().map(|f| &f.ty).collect::<Vec<_>>();
+    let integer_types = fields.iter().filter(|f| f.integer).map(|f| &f.ty);
+    let attributes = fields.iter().map(|f| {
+        let Field { ident: field, ty, name, normalized, integer } = f;
+        quote! {
+            crate::gl::VertexAttribute {
+                name: #name,
+                offset: ::std::mem::offset_of!(#ident, #field),
+                size: <#ty as crate::gl::VertexComponent>::COMPONENT_COUNT,
+                stride: ::std::mem::size_of::<#ident>(),
+                component_type: <#ty as crate::gl::VertexComponent>::COMPONENT_TYPE,
+                normalized: #normalized,
+                integer: #integer,
+            }
+        }
+    });
+
+    Ok(quote! {
+        // Pod requires every field to be Pod and no padding between them.
+        const _: () = {
+            fn assert_pod<T: crate::gl::Pod>() {}
+            #[allow(dead_code)]
+            fn assert_fields_are_pod() {
+                #(assert_pod::<#types>();)*
+            }
+            assert!(
+                ::std::mem::size_of::<#ident>() == 0 #(+ ::std::mem::size_of::<#types>())*,
+                "Vertex structs must not have padding"
+            );
+            #(assert!(
+                <#integer_types as crate::gl::VertexComponent>::COMPONENT_TYPE.is_integer(),
+                "#[vertex(integer)] requires integer components"
+            );)*
+        };
+
+        unsafe impl crate::gl::Pod for #ident {}
+
+        impl crate::gl::Vertex for #ident {
+            const ATTRIBUTES: &'static [crate::gl::VertexAttribute] = &[
+                #(#attributes),*
+            ];
+        }
+    })
+}
+
+fn has_repr_c(input: &DeriveInput) -> bool {
+    input.attrs.iter().filter(|a| a.path().is_ident("repr")).any(|a| {
+        let mut c = false;
+        let _ = a.parse_nested_meta(|meta| {
+            if meta.path.is_ident("C") {
+                c = true;
+            }
+            Ok(())
+        });
+        c
+    })
+}
+
+fn parse_field(field: &syn::Field) -> Result<Field, Error> {
+    let ident = field.ident.clone().expect("named field");
+    let mut name = None;
+    let mut normalized = false;
+    let mut integer = false;
+
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("vertex")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                name = Some(meta.value()?.parse::<LitStr>()?.value());
+                Ok(())
+            } else if meta.path.is_ident("normalized") {
+                normalized = true;
+                Ok(())
+            } else if meta.path.is_ident("integer") {
+                integer = true;
+                Ok(())
+            } else {
+                Err(meta.error("unknown vertex attribute"))
+            }
+        })?;
+    }
+    if normalized && integer {
+        return Err(Error::new_spanned(field, "a vertex attribute cannot be both normalized and integer"));
+    }
+
+    Ok(Field {
+        name: name.unwrap_or_else(|| lower_camel_case(&ident.to_string())),
+        ident,
+        ty: field.ty.clone(),
+        normalized,
+        integer,
+    })
+}
+
+fn lower_camel_case(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut upper = false;
+    for c in s.chars() {
+        if c == '_' {
+            upper = !out.is_empty();
+        } else if upper {
+            out.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
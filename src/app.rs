@@ -1,5 +1,5 @@
-use crate::{error::Error, gl::{ColoredSliceTriangleStrip, FrameBuffer, Primitive, Shader, Sprite, SpriteBatch, GL}, rect, vec3, vec4};
-use crate::math::Matrix4;
+use crate::{error::Error, gl::{mesh::Mesh, DepthConvention, FrameBuffer, Primitive, Shader, Sprite, SpriteBatch, GL}, rect, vec3, vec4};
+use crate::math::{curve::Easing, Color, Frustum, Matrix4, Quaternion};
 use log::error;
 use wasm_bindgen::prelude::*;
 use web_sys::WebGl2RenderingContext;
@@ -16,18 +16,19 @@ pub struct App {
 impl App {
     pub fn init(id: &str) -> Result<App, Error> {
         let gl = GL::init(id)?;
+        gl.set_depth_convention(DepthConvention::ReverseZ);
 
         // Prepearing Off-screen Buffer
         let frame_buffer = FrameBuffer::new(&gl, gl.screen_size())?;
 
         let vert_shader_source = r##"#version 300 es
             in vec4 position;
-            in vec4 color;
+            in vec3 normal;
             uniform mat4 mvp;
             out vec4 vColor;
             void main() {
                 gl_Position = mvp * position;
-                vColor = color;
+                vColor = vec4(normal * 0.5 + 0.5, 1.0);
             }
             "##;
         let frag_shader_source = r##"#version 300 es
@@ -40,37 +41,7 @@ impl App {
             "##;
         let cube_shader = Shader::new(&gl, vert_shader_source, frag_shader_source)?;
         
-        #[rustfmt::skip]
-        let cube = [
-            // http://www.cs.umd.edu/gvil/papers/av_ts.pdf
-            //
-            //    1---------2
-            //   /|        /|
-            //  / |       / |
-            // 3---------4  |
-            // |  5- - - |- 6
-            // | /       | /
-            // |/        |/
-            // 8---------7
-            //                          
-            // 4 3 7 8 5 3 1 4 2 7 6 5 2 1 
-            (vec3!( 0.5,  0.5,  0.5), vec4!(0.0, 1.0, 0.0, 1.0)), // 4
-            (vec3!(-0.5,  0.5,  0.5), vec4!(0.0, 0.0, 1.0, 1.0)), // 3
-            (vec3!( 0.5, -0.5,  0.5), vec4!(1.0, 0.0, 0.0, 1.0)), // 7
-            (vec3!(-0.5, -0.5,  0.5), vec4!(0.0, 1.0, 0.0, 1.0)), // 8
-            (vec3!(-0.5, -0.5, -0.5), vec4!(0.0, 0.0, 1.0, 1.0)), // 5
-            (vec3!(-0.5,  0.5,  0.5), vec4!(1.0, 0.0, 0.0, 1.0)), // 3
-            (vec3!(-0.5,  0.5, -0.5), vec4!(0.0, 1.0, 0.0, 1.0)), // 1
-            (vec3!( 0.5,  0.5,  0.5), vec4!(0.0, 0.0, 1.0, 1.0)), // 4
-            (vec3!( 0.5,  0.5, -0.5), vec4!(1.0, 0.0, 0.0, 1.0)), // 2
-            (vec3!( 0.5, -0.5,  0.5), vec4!(0.0, 1.0, 0.0, 1.0)), // 7
-            (vec3!( 0.5, -0.5, -0.5), vec4!(0.0, 0.0, 1.0, 1.0)), // 6
-            (vec3!(-0.5, -0.5, -0.5), vec4!(1.0, 0.0, 0.0, 1.0)), // 5
-            (vec3!( 0.5,  0.5, -0.5), vec4!(0.0, 1.0, 0.0, 1.0)), // 2
-            (vec3!(-0.5,  0.5, -0.5), vec4!(0.0, 0.0, 1.0, 1.0)), // 1
-        ];
-        let cube = ColoredSliceTriangleStrip(&cube);
-        let cube = Primitive::new(&gl, cube)?;
+        let cube = Mesh::cube(1.0).upload(&gl)?;
         cube_shader.enable_vertex_attribute(&cube);
 
         let sprite = Sprite::new(&gl, gl.screen_size())?;
@@ -100,18 +71,20 @@ impl App {
         Self::request_animation_frame(clo).unwrap();
     }
 
-    fn tick(self, _performance_clock_time: f64) -> Result<Self, JsValue> {
+    fn tick(mut self, _performance_clock_time: f64) -> Result<Self, JsValue> {
         let context = self.gl.context();
 
-        let deg = (self.counter % 360) as f32 / 180.0 * 2.0 * std::f32::consts::PI;
+        // Two turns per cycle, slowing down around the point where the cycle wraps.
+        let t = (self.counter % 360) as f32 / 360.0;
+        let deg = Easing::SineInOut.ease(t) * 4.0 * std::f32::consts::PI;
 
         let world = 
-            Matrix4::yaw_rotation(deg) * Matrix4::pitch_rotation(deg)
+            Matrix4::from(Quaternion::from_euler(deg, deg, 0.0))
             * Matrix4::scaling(4.0, 4.0, 4.0);
 
         let fov_y = 45.0/180.0 * std::f32::consts::PI;
         let pv = 
-            Matrix4::perspective_fov(fov_y, self.gl.screen_aspect_ratio(), 0.1, 5000.0) *
+            Matrix4::perspective_reverse_z(fov_y, self.gl.screen_aspect_ratio(), 0.1, 5000.0) *
             Matrix4::look_at(
                 vec3!(0.0, 0.0, 10.0),
                 vec3!(0.0, 0.0, 0.0),
@@ -119,33 +92,42 @@ impl App {
 
         let pv = pv * world;
 
+        // pv includes the world transform, so the frustum is in the cube's model space.
+        let frustum = Frustum::from(pv);
+        let cube_visible = self.cube.bounding_box()
+            .map_or(true, |b| frustum.intersects_aabb(&b));
+
         self.gl.bind_framebuffer(&self.frame_buffer);
         self.gl.clear((1.0, 0.0, 1.0, 0.0));
-        self.gl.clear_depth(0.0);
+        self.gl.clear_depth_to_far();
         self.gl.clear_stencil(1);
 
-        context.enable(WebGl2RenderingContext::DEPTH_TEST);
+        self.gl.enable_depth_test();
         context.enable(WebGl2RenderingContext::CULL_FACE);
-        self.cube_shader.enable();
-        self.cube_shader.set_uniform_model_view_perspective(&pv);
-        self.cube_shader.draw(&self.cube);
-        self.cube_shader.disable();
+        if cube_visible {
+            self.cube_shader.enable();
+            self.cube_shader.set_uniform_model_view_perspective(&pv);
+            self.cube_shader.draw(&self.cube);
+            self.cube_shader.disable();
+        }
         context.finish();
 
         self.gl.bind_framebuffer(self.gl.screen());
-        self.gl.clear((0.0, 0.0, 0.0, 1.0));
-        self.gl.clear_depth(0.0);
+        self.gl.clear(Color::BLACK);
+        self.gl.clear_depth_to_far();
         self.gl.clear_stencil(1);
 
-        context.enable(WebGl2RenderingContext::DEPTH_TEST);
+        self.gl.enable_depth_test();
         context.enable(WebGl2RenderingContext::CULL_FACE);
         context.enable(WebGl2RenderingContext::BLEND);
         context.blend_func(WebGl2RenderingContext::SRC_ALPHA, WebGl2RenderingContext::ONE_MINUS_SRC_ALPHA);
 
-        self.cube_shader.enable();
-        self.cube_shader.set_uniform_model_view_perspective(&pv);
-        self.cube_shader.draw(&self.cube);
-        self.cube_shader.disable();
+        if cube_visible {
+            self.cube_shader.enable();
+            self.cube_shader.set_uniform_model_view_perspective(&pv);
+            self.cube_shader.draw(&self.cube);
+            self.cube_shader.disable();
+        }
 
         let mut batch = SpriteBatch::new();
         batch.add(self.frame_buffer.texture(), vec4!(0.0, 0.0, 1.0, 1.0), rect!(0, 0, 256, 256));
@@ -155,7 +137,7 @@ impl App {
         context.enable(WebGl2RenderingContext::BLEND);
         context.blend_func(WebGl2RenderingContext::SRC_ALPHA, WebGl2RenderingContext::ONE_MINUS_SRC_ALPHA);
         
-        self.sprite.draw(batch);
+        self.sprite.draw(batch)?;
 
         context.finish();
 
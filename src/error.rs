@@ -25,6 +25,12 @@ impl From<JsValue> for Error {
     }
 }
 
+impl From<Error> for JsValue {
+    fn from(value: Error) -> Self {
+        JsValue::from_str(&value.message)
+    }
+}
+
 impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.message)
@@ -1,10 +1,21 @@
+mod dynamic_primitive;
 mod gl;
+pub mod gltf;
+pub mod mesh;
+pub mod obj;
+pub mod optimize;
 mod primitive;
 mod shader;
 mod sprite;
 mod screen;
+mod vertex;
+pub use dynamic_primitive::*;
 pub use gl::*;
 pub use primitive::*;
 pub use shader::*;
 pub use sprite::*;
 pub use screen::*;
+pub use vertex::*;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,313 @@
+use std::marker::PhantomData;
+use std::mem::size_of;
+
+use web_sys::WebGl2RenderingContext;
+use web_sys::WebGlBuffer;
+use web_sys::WebGlVertexArrayObject;
+
+use crate::error::Error;
+use super::cast_slice;
+use super::DrawArrayMode;
+use super::Drawable;
+use super::IndexType;
+use super::Vertex;
+use super::VertexAttribute;
+use super::GL;
+
+// Hint for how often the contents of a buffer are replaced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BufferUsage {
+    // Uploaded once, drawn many times.
+    Static,
+    // Updated now and then, drawn many times.
+    Dynamic,
+    // Updated every frame, drawn a few times.
+    Stream,
+}
+
+impl From<BufferUsage> for u32 {
+    fn from(val: BufferUsage) -> Self {
+        match val {
+            BufferUsage::Static => WebGl2RenderingContext::STATIC_DRAW,
+            BufferUsage::Dynamic => WebGl2RenderingContext::DYNAMIC_DRAW,
+            BufferUsage::Stream => WebGl2RenderingContext::STREAM_DRAW,
+        }
+    }
+}
+
+const MIN_CAPACITY: usize = 64;
+
+// Capacity (in vertices) after growing to hold at least `required` vertices.
+// Doubles so that repeated small growths stay amortized O(1).
+pub fn grown_capacity(capacity: usize, required: usize) -> usize {
+    if required <= capacity {
+        return capacity;
+    }
+    required.max(capacity * 2).max(MIN_CAPACITY).next_power_of_two()
+}
+
+// Where a ring buffer places `count` vertices when its write cursor is at `cursor`.
+// Returns the first vertex and whether the buffer has to be orphaned first,
+// i.e. the write wraps around onto data that may still be in use by the GPU.
+pub fn ring_placement(cursor: usize, capacity: usize, count: usize) -> (usize, bool) {
+    if cursor + count <= capacity {
+        (cursor, false)
+    } else {
+        (0, true)
+    }
+}
+
+// Drawn range (first, count) after writing `count` vertices at `first` into the
+// drawn range (drawn_first, drawn_count). An empty range is replaced by the
+// written one. Writes must overlap or touch the drawn range, since the gap
+// between them holds vertices that were never written.
+pub fn updated_range(drawn_first: usize, drawn_count: usize, first: usize, count: usize) -> Result<(usize, usize), Error> {
+    if count == 0 {
+        return Ok((drawn_first, drawn_count));
+    }
+    if drawn_count == 0 {
+        return Ok((first, count));
+    }
+    let (drawn_end, end) = (drawn_first + drawn_count, first + count);
+    if first > drawn_end || end < drawn_first {
+        return Err(format!(
+            "update of vertices {}..{} is not adjacent to the drawn range {}..{}",
+            first, end, drawn_first, drawn_end).into());
+    }
+    let new_first = drawn_first.min(first);
+    Ok((new_first, drawn_end.max(end) - new_first))
+}
+
+// Vertex buffer whose contents can be updated after creation.
+//
+// The buffer object is kept across capacity growth, so attribute pointers set by
+// `Shader::enable_vertex_attribute` stay valid and only have to be set once.
+pub struct DynamicPrimitive<V: Vertex> {
+    gl: GL,
+    vao: WebGlVertexArrayObject,
+    buffer: WebGlBuffer,
+    usage: BufferUsage,
+    draw_array_mode: DrawArrayMode,
+    // In vertices.
+    capacity: usize,
+    first_vertex: usize,
+    vertex_count: usize,
+    // Next write position of `stream`.
+    cursor: usize,
+    _phantom_data: PhantomData<V>,
+}
+
+#[allow(dead_code)]
+impl<V: Vertex> DynamicPrimitive<V> {
+    pub fn new(
+        gl: &GL,
+        draw_array_mode: DrawArrayMode,
+        usage: BufferUsage,
+        capacity: usize,
+    ) -> Result<DynamicPrimitive<V>, Error> {
+        let ctx = gl.context();
+        let vao = ctx
+            .create_vertex_array()
+            .ok_or("createVertexArray failed.")?;
+        let buffer = ctx
+            .create_buffer()
+            .ok_or("createBuffer failed.")?;
+
+        let primitive = DynamicPrimitive {
+            gl: gl.clone(),
+            vao,
+            buffer,
+            usage,
+            draw_array_mode,
+            capacity,
+            first_vertex: 0,
+            vertex_count: 0,
+            cursor: 0,
+            _phantom_data: PhantomData,
+        };
+        primitive.allocate();
+        Ok(primitive)
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn usage(&self) -> BufferUsage {
+        self.usage
+    }
+
+    fn bind(&self) {
+        let ctx = self.gl.context();
+        ctx.bind_vertex_array(Some(&self.vao));
+        ctx.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, Some(&self.buffer));
+    }
+
+    fn unbind(&self) {
+        self.gl.context().bind_vertex_array(None);
+    }
+
+    // Allocates new, uninitialized storage of `capacity` vertices.
+    fn allocate(&self) {
+        self.bind();
+        self.gl.context().buffer_data_with_i32(
+            WebGl2RenderingContext::ARRAY_BUFFER,
+            (self.capacity * size_of::<V>()) as i32,
+            self.usage.into(),
+        );
+        self.unbind();
+    }
+
+    // Grows the buffer to hold at least `required` vertices, keeping the contents.
+    pub fn reserve(&mut self, required: usize) -> Result<(), Error> {
+        let capacity = grown_capacity(self.capacity, required);
+        if capacity == self.capacity {
+            return Ok(());
+        }
+
+        let ctx = self.gl.context();
+        let used = ((self.first_vertex + self.vertex_count).max(self.cursor) * size_of::<V>()) as i32;
+        let scratch = if used > 0 {
+            // Reallocating discards the storage, so park the contents in a scratch buffer.
+            let scratch = ctx.create_buffer().ok_or("createBuffer failed.")?;
+            ctx.bind_buffer(WebGl2RenderingContext::COPY_READ_BUFFER, Some(&self.buffer));
+            ctx.bind_buffer(WebGl2RenderingContext::COPY_WRITE_BUFFER, Some(&scratch));
+            ctx.buffer_data_with_i32(WebGl2RenderingContext::COPY_WRITE_BUFFER, used, WebGl2RenderingContext::STREAM_COPY);
+            ctx.copy_buffer_sub_data_with_i32_and_i32_and_i32(
+                WebGl2RenderingContext::COPY_READ_BUFFER,
+                WebGl2RenderingContext::COPY_WRITE_BUFFER,
+                0, 0, used);
+            Some(scratch)
+        } else {
+            None
+        };
+
+        self.capacity = capacity;
+        self.allocate();
+
+        if let Some(scratch) = scratch {
+            ctx.bind_buffer(WebGl2RenderingContext::COPY_READ_BUFFER, Some(&scratch));
+            ctx.bind_buffer(WebGl2RenderingContext::COPY_WRITE_BUFFER, Some(&self.buffer));
+            ctx.copy_buffer_sub_data_with_i32_and_i32_and_i32(
+                WebGl2RenderingContext::COPY_READ_BUFFER,
+                WebGl2RenderingContext::COPY_WRITE_BUFFER,
+                0, 0, used);
+            ctx.bind_buffer(WebGl2RenderingContext::COPY_READ_BUFFER, None);
+            ctx.bind_buffer(WebGl2RenderingContext::COPY_WRITE_BUFFER, None);
+            ctx.delete_buffer(Some(&scratch));
+        }
+        Ok(())
+    }
+
+    fn write(&self, first_vertex: usize, vertices: &[V]) {
+        self.bind();
+        self.gl.context().buffer_sub_data_with_i32_and_u8_array(
+            WebGl2RenderingContext::ARRAY_BUFFER,
+            (first_vertex * size_of::<V>()) as i32,
+            cast_slice(vertices),
+        );
+        self.unbind();
+    }
+
+    // Overwrites vertices starting at `first_vertex` with bufferSubData.
+    // The drawn range is extended to cover them; see `updated_range`.
+    pub fn update(&mut self, first_vertex: usize, vertices: &[V]) -> Result<(), Error> {
+        let (drawn_first, drawn_count) =
+            updated_range(self.first_vertex, self.vertex_count, first_vertex, vertices.len())?;
+        self.reserve(first_vertex + vertices.len())?;
+        self.write(first_vertex, vertices);
+
+        self.first_vertex = drawn_first;
+        self.vertex_count = drawn_count;
+        Ok(())
+    }
+
+    // Replaces the whole contents with `vertices`.
+    pub fn set(&mut self, vertices: &[V]) -> Result<(), Error> {
+        self.reserve(vertices.len())?;
+        self.write(0, vertices);
+        self.first_vertex = 0;
+        self.vertex_count = vertices.len();
+        self.cursor = 0;
+        Ok(())
+    }
+
+    // Detaches the current storage so the driver does not have to wait for
+    // draws still reading from it. The contents become undefined.
+    pub fn orphan(&mut self) {
+        self.allocate();
+        self.first_vertex = 0;
+        self.vertex_count = 0;
+        self.cursor = 0;
+    }
+
+    // Appends per-frame geometry to the ring buffer and makes it the drawn range.
+    // Earlier ranges are left untouched until the ring wraps around, at which
+    // point the buffer is orphaned instead of overwritten.
+    pub fn stream(&mut self, vertices: &[V]) -> Result<(), Error> {
+        if vertices.len() > self.capacity {
+            // Old ranges are not needed anymore; skip copying them while growing.
+            self.first_vertex = 0;
+            self.vertex_count = 0;
+            self.cursor = 0;
+            self.reserve(vertices.len())?;
+        }
+
+        let (first_vertex, orphan) = ring_placement(self.cursor, self.capacity, vertices.len());
+        if orphan {
+            self.orphan();
+        }
+        self.write(first_vertex, vertices);
+
+        self.first_vertex = first_vertex;
+        self.vertex_count = vertices.len();
+        self.cursor = first_vertex + vertices.len();
+        Ok(())
+    }
+}
+
+impl<V: Vertex> Drawable for DynamicPrimitive<V> {
+    fn draw_array_mode(&self) -> DrawArrayMode {
+        self.draw_array_mode
+    }
+
+    fn vertex_streams(&self) -> Vec<(&WebGlBuffer, &'static [VertexAttribute])> {
+        vec![(&self.buffer, V::ATTRIBUTES)]
+    }
+
+    fn instance_attributes(&self) -> Option<(&WebGlBuffer, &'static [VertexAttribute])> {
+        None
+    }
+
+    fn instance_count(&self) -> Option<i32> {
+        None
+    }
+
+    fn first_vertex(&self) -> i32 {
+        self.first_vertex as i32
+    }
+
+    fn vertex_count(&self) -> i32 {
+        self.vertex_count as i32
+    }
+
+    fn index_type(&self) -> Option<IndexType> {
+        None
+    }
+
+    fn index_count(&self) -> i32 {
+        0
+    }
+
+    fn vertex_array_object(&self) -> &WebGlVertexArrayObject {
+        &self.vao
+    }
+}
+
+impl<V: Vertex> Drop for DynamicPrimitive<V> {
+    fn drop(&mut self) {
+        let ctx = self.gl.context();
+        ctx.delete_vertex_array(Some(&self.vao));
+        ctx.delete_buffer(Some(&self.buffer));
+    }
+}
@@ -1,4 +1,4 @@
-use std::rc::Rc;
+use std::{cell::Cell, rc::Rc};
 
 
 use web_sys::{HtmlCanvasElement, WebGl2RenderingContext};
@@ -19,10 +19,39 @@ impl Screen for DefaultScreen {
     }
 }
 
+// Which end of the depth range the projection matrix maps the near plane to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DepthConvention {
+    // Near plane at depth 0, far plane at depth 1.
+    // Matrix4::perspective_fov, perspective_infinite and orthographic.
+    Standard,
+    // Near plane at depth 1, far plane at depth 0.
+    // Matrix4::perspective_reverse_z and perspective_infinite_reverse_z.
+    ReverseZ,
+}
+
+impl DepthConvention {
+    // Depth value the depth buffer should be cleared to.
+    pub fn far_depth(self) -> f32 {
+        match self {
+            DepthConvention::Standard => 1.0,
+            DepthConvention::ReverseZ => 0.0,
+        }
+    }
+
+    pub fn depth_func(self) -> u32 {
+        match self {
+            DepthConvention::Standard => WebGl2RenderingContext::LEQUAL,
+            DepthConvention::ReverseZ => WebGl2RenderingContext::GEQUAL,
+        }
+    }
+}
+
 struct Inner {
     element: HtmlCanvasElement,
     context: WebGl2RenderingContext,
     screen: DefaultScreen,
+    depth_convention: Cell<DepthConvention>,
 }
 
 impl GL {
@@ -45,7 +74,12 @@ impl GL {
         Self::ensure_extension(&context, "EXT_color_buffer_float")?;
 
         Ok(GL(
-            Rc::new(Inner { element: canvas, context, screen: DefaultScreen{} })
+            Rc::new(Inner {
+                element: canvas,
+                context,
+                screen: DefaultScreen{},
+                depth_convention: Cell::new(DepthConvention::Standard),
+            })
         ))
     }
 
@@ -73,7 +107,26 @@ impl GL {
 
     pub fn clear_depth(&self, depth: f32) {
         self.0.context.clear_depth(depth);
-        self.0.context.clear(WebGl2RenderingContext::DEPTH_BITS);
+        self.0.context.clear(WebGl2RenderingContext::DEPTH_BUFFER_BIT);
+    }
+
+    // Clears the depth buffer to the far plane of the current depth convention.
+    pub fn clear_depth_to_far(&self) {
+        self.clear_depth(self.depth_convention().far_depth());
+    }
+
+    pub fn depth_convention(&self) -> DepthConvention {
+        self.0.depth_convention.get()
+    }
+
+    pub fn set_depth_convention(&self, convention: DepthConvention) {
+        self.0.depth_convention.set(convention);
+    }
+
+    // Enables depth testing with the comparison matching the current depth convention.
+    pub fn enable_depth_test(&self) {
+        self.0.context.enable(WebGl2RenderingContext::DEPTH_TEST);
+        self.0.context.depth_func(self.depth_convention().depth_func());
     }
 
     pub fn context(&self) -> &WebGl2RenderingContext {
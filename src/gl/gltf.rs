@@ -0,0 +1,1028 @@
+use std::convert::TryInto;
+
+use web_sys::{ImageBitmap, WebGl2RenderingContext, WebGlTexture};
+
+use crate::error::Error;
+use crate::math::{Color, Matrix4, Quaternion, Vector2, Vector3, Vector4};
+use crate::{mat4, vec2, vec3, vec4};
+
+use super::mesh::face_normal;
+use super::{
+    DrawArrayMode, Primitive, SliceLineLoop, SliceLineStrip, SliceLines, SlicePoints, SliceTriangleFan,
+    SliceTriangleList, SliceTriangleStrip, Vertex, VertexAttributeArray, GL,
+};
+
+// A glTF 2.0 asset decoded into plain data.
+//
+// Everything the asset refers to is loaded eagerly: buffers and images given
+// as data URIs are decoded, GLB binary chunks are used as is, and any other
+// URI is passed to the caller's `load` function (typically a relative path to
+// a file fetched beforehand). Images are kept encoded: decoding is asynchronous
+// in the browser (e.g. `createImageBitmap` on a Blob of `GltfImage::data`), so
+// it is left to the caller, and `GltfTexture::upload` takes the decoded images.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Gltf {
+    pub meshes: Vec<GltfMesh>,
+    pub materials: Vec<GltfMaterial>,
+    pub textures: Vec<GltfTexture>,
+    pub images: Vec<GltfImage>,
+    pub nodes: Vec<GltfNode>,
+    // Root nodes of the default scene, or of the first scene if none is marked.
+    pub scene: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GltfMesh {
+    pub name: Option<String>,
+    pub primitives: Vec<GltfPrimitive>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfPrimitive {
+    pub draw_array_mode: DrawArrayMode,
+    pub vertices: Vec<GltfVertex>,
+    pub indices: Option<Vec<u32>>,
+    pub material: Option<usize>,
+}
+
+// Attributes missing from the asset keep their defaults: zero, except for a
+// white color and flat normals for triangle lists.
+#[derive(Debug, Copy, Clone, PartialEq, Vertex)]
+#[repr(C)]
+pub struct GltfVertex {
+    pub position: Vector3,
+    pub normal: Vector3,
+    pub tangent: Vector4,
+    #[vertex(name = "textureCoord")]
+    pub texture_coordination: Vector2,
+    pub color: Color,
+    #[vertex(integer)]
+    pub joints: [u16; 4],
+    pub weights: Vector4,
+}
+
+impl Default for GltfVertex {
+    fn default() -> Self {
+        GltfVertex {
+            position: vec3!(0.0, 0.0, 0.0),
+            normal: vec3!(0.0, 0.0, 0.0),
+            tangent: vec4!(0.0, 0.0, 0.0, 0.0),
+            texture_coordination: vec2!(0.0, 0.0),
+            color: Color::WHITE,
+            joints: [0; 4],
+            weights: vec4!(0.0, 0.0, 0.0, 0.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfNode {
+    pub name: Option<String>,
+    pub mesh: Option<usize>,
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+    // Relative to the parent.
+    pub transform: Matrix4,
+    // Relative to the scene root.
+    pub world_transform: Matrix4,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AlphaMode {
+    Opaque,
+    // Fragments with alpha below the cutoff are discarded.
+    Mask(f32),
+    Blend,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GltfTextureRef {
+    pub texture: usize,
+    // Which TEXCOORD_n set to sample with. Only set 0 is imported.
+    pub texture_coordination: usize,
+}
+
+// Metallic-roughness PBR material.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfMaterial {
+    pub name: Option<String>,
+    pub base_color: Color,
+    pub base_color_texture: Option<GltfTextureRef>,
+    pub metallic: f32,
+    pub roughness: f32,
+    pub metallic_roughness_texture: Option<GltfTextureRef>,
+    pub normal_texture: Option<GltfTextureRef>,
+    pub normal_scale: f32,
+    pub occlusion_texture: Option<GltfTextureRef>,
+    pub occlusion_strength: f32,
+    pub emissive: Vector3,
+    pub emissive_texture: Option<GltfTextureRef>,
+    pub alpha_mode: AlphaMode,
+    pub double_sided: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GltfTexture {
+    pub image: Option<usize>,
+    pub sampler: GltfSampler,
+}
+
+// Filters and wrap modes are WebGL enums, as in the glTF file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GltfSampler {
+    pub mag_filter: Option<u32>,
+    pub min_filter: Option<u32>,
+    pub wrap_s: u32,
+    pub wrap_t: u32,
+}
+
+#[allow(dead_code)]
+impl GltfSampler {
+    // Filters the asset leaves open default to linear, with mipmaps.
+    pub fn mag_filter_or_default(&self) -> u32 {
+        self.mag_filter.unwrap_or(WebGl2RenderingContext::LINEAR)
+    }
+
+    pub fn min_filter_or_default(&self) -> u32 {
+        self.min_filter.unwrap_or(WebGl2RenderingContext::LINEAR_MIPMAP_LINEAR)
+    }
+
+    // Whether the minification filter samples mipmaps, which must then be generated.
+    pub fn uses_mipmaps(&self) -> bool {
+        !matches!(self.min_filter_or_default(), WebGl2RenderingContext::NEAREST | WebGl2RenderingContext::LINEAR)
+    }
+}
+
+#[allow(dead_code)]
+impl GltfTexture {
+    // Creates a WebGL texture from the texture's image, with the filters and
+    // wrap modes of its sampler. `images` are `Gltf::images` decoded, in the
+    // same order.
+    pub fn upload(&self, gl: &GL, images: &[ImageBitmap]) -> Result<WebGlTexture, Error> {
+        let image = self.image.and_then(|n| images.get(n))
+            .ok_or_else(|| Error::from("gltf: texture without a decoded image"))?;
+
+        let ctx = gl.context();
+        let texture = ctx.create_texture().ok_or("createTexture failed.")?;
+        let target = WebGl2RenderingContext::TEXTURE_2D;
+        ctx.bind_texture(target, Some(&texture));
+        let uploaded = ctx.tex_image_2d_with_u32_and_u32_and_image_bitmap(
+            target,
+            0,
+            WebGl2RenderingContext::RGBA as i32,
+            WebGl2RenderingContext::RGBA,
+            WebGl2RenderingContext::UNSIGNED_BYTE,
+            image,
+        );
+        if let Err(e) = uploaded {
+            ctx.bind_texture(target, None);
+            ctx.delete_texture(Some(&texture));
+            return Err(e.into());
+        }
+
+        let sampler = &self.sampler;
+        ctx.tex_parameteri(target, WebGl2RenderingContext::TEXTURE_MAG_FILTER, sampler.mag_filter_or_default() as i32);
+        ctx.tex_parameteri(target, WebGl2RenderingContext::TEXTURE_MIN_FILTER, sampler.min_filter_or_default() as i32);
+        ctx.tex_parameteri(target, WebGl2RenderingContext::TEXTURE_WRAP_S, sampler.wrap_s as i32);
+        ctx.tex_parameteri(target, WebGl2RenderingContext::TEXTURE_WRAP_T, sampler.wrap_t as i32);
+        if sampler.uses_mipmaps() {
+            ctx.generate_mipmap(target);
+        }
+        ctx.bind_texture(target, None);
+        Ok(texture)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfImage {
+    pub name: Option<String>,
+    pub mime_type: Option<String>,
+    // Encoded image file, e.g. PNG or JPEG.
+    pub data: Vec<u8>,
+}
+
+// The JSON schema, as far as it is imported.
+mod schema {
+    use std::collections::HashMap;
+
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Document {
+        pub asset: Asset,
+        pub scene: Option<usize>,
+        #[serde(default)]
+        pub scenes: Vec<Scene>,
+        #[serde(default)]
+        pub nodes: Vec<Node>,
+        #[serde(default)]
+        pub meshes: Vec<Mesh>,
+        #[serde(default)]
+        pub accessors: Vec<Accessor>,
+        #[serde(default)]
+        pub buffer_views: Vec<BufferView>,
+        #[serde(default)]
+        pub buffers: Vec<Buffer>,
+        #[serde(default)]
+        pub materials: Vec<Material>,
+        #[serde(default)]
+        pub textures: Vec<Texture>,
+        #[serde(default)]
+        pub images: Vec<Image>,
+        #[serde(default)]
+        pub samplers: Vec<Sampler>,
+        #[serde(default)]
+        pub extensions_required: Vec<String>,
+    }
+
+    #[derive(Deserialize)]
+    pub struct Asset {
+        pub version: String,
+    }
+
+    #[derive(Deserialize)]
+    pub struct Scene {
+        #[serde(default)]
+        pub nodes: Vec<usize>,
+    }
+
+    #[derive(Deserialize)]
+    pub struct Node {
+        pub name: Option<String>,
+        #[serde(default)]
+        pub children: Vec<usize>,
+        pub mesh: Option<usize>,
+        // Column-major.
+        pub matrix: Option<[f32; 16]>,
+        pub translation: Option<[f32; 3]>,
+        pub rotation: Option<[f32; 4]>,
+        pub scale: Option<[f32; 3]>,
+    }
+
+    #[derive(Deserialize)]
+    pub struct Mesh {
+        pub name: Option<String>,
+        pub primitives: Vec<Primitive>,
+    }
+
+    #[derive(Deserialize)]
+    pub struct Primitive {
+        pub attributes: HashMap<String, usize>,
+        pub indices: Option<usize>,
+        pub material: Option<usize>,
+        #[serde(default = "triangles")]
+        pub mode: u32,
+    }
+
+    fn triangles() -> u32 {
+        4
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Accessor {
+        pub buffer_view: Option<usize>,
+        #[serde(default)]
+        pub byte_offset: usize,
+        pub component_type: u32,
+        #[serde(default)]
+        pub normalized: bool,
+        pub count: usize,
+        #[serde(rename = "type")]
+        pub kind: String,
+        pub sparse: Option<Sparse>,
+    }
+
+    #[derive(Deserialize)]
+    pub struct Sparse {
+        pub count: usize,
+        pub indices: SparseIndices,
+        pub values: SparseValues,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SparseIndices {
+        pub buffer_view: usize,
+        #[serde(default)]
+        pub byte_offset: usize,
+        pub component_type: u32,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SparseValues {
+        pub buffer_view: usize,
+        #[serde(default)]
+        pub byte_offset: usize,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct BufferView {
+        pub buffer: usize,
+        #[serde(default)]
+        pub byte_offset: usize,
+        pub byte_length: usize,
+        pub byte_stride: Option<usize>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Buffer {
+        pub uri: Option<String>,
+        pub byte_length: usize,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Material {
+        pub name: Option<String>,
+        pub pbr_metallic_roughness: Option<PbrMetallicRoughness>,
+        pub normal_texture: Option<TextureInfo>,
+        pub occlusion_texture: Option<TextureInfo>,
+        pub emissive_texture: Option<TextureInfo>,
+        #[serde(default)]
+        pub emissive_factor: [f32; 3],
+        #[serde(default = "opaque")]
+        pub alpha_mode: String,
+        #[serde(default = "half")]
+        pub alpha_cutoff: f32,
+        #[serde(default)]
+        pub double_sided: bool,
+    }
+
+    fn opaque() -> String {
+        "OPAQUE".to_string()
+    }
+
+    fn half() -> f32 {
+        0.5
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct PbrMetallicRoughness {
+        #[serde(default = "ones")]
+        pub base_color_factor: [f32; 4],
+        pub base_color_texture: Option<TextureInfo>,
+        #[serde(default = "one")]
+        pub metallic_factor: f32,
+        #[serde(default = "one")]
+        pub roughness_factor: f32,
+        pub metallic_roughness_texture: Option<TextureInfo>,
+    }
+
+    fn one() -> f32 {
+        1.0
+    }
+
+    fn ones() -> [f32; 4] {
+        [1.0; 4]
+    }
+
+    // Also covers normal (scale) and occlusion (strength) texture infos.
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct TextureInfo {
+        pub index: usize,
+        #[serde(default)]
+        pub tex_coord: usize,
+        #[serde(default = "one")]
+        pub scale: f32,
+        #[serde(default = "one")]
+        pub strength: f32,
+    }
+
+    #[derive(Deserialize)]
+    pub struct Texture {
+        pub sampler: Option<usize>,
+        pub source: Option<usize>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Image {
+        pub name: Option<String>,
+        pub uri: Option<String>,
+        pub mime_type: Option<String>,
+        pub buffer_view: Option<usize>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Sampler {
+        pub mag_filter: Option<u32>,
+        pub min_filter: Option<u32>,
+        #[serde(default = "repeat")]
+        pub wrap_s: u32,
+        #[serde(default = "repeat")]
+        pub wrap_t: u32,
+    }
+
+    fn repeat() -> u32 {
+        10497
+    }
+}
+
+const GLB_MAGIC: u32 = 0x4654_6c67;
+const GLB_CHUNK_JSON: u32 = 0x4e4f_534a;
+const GLB_CHUNK_BIN: u32 = 0x004e_4942;
+
+const BYTE: u32 = 5120;
+const UNSIGNED_BYTE: u32 = 5121;
+const SHORT: u32 = 5122;
+const UNSIGNED_SHORT: u32 = 5123;
+const UNSIGNED_INT: u32 = 5125;
+const FLOAT: u32 = 5126;
+
+#[allow(dead_code)]
+impl Gltf {
+    // Imports a .gltf file. `load` returns the contents of an external URI.
+    pub fn from_gltf<F>(json: &[u8], load: F) -> Result<Gltf, Error>
+    where
+        F: FnMut(&str) -> Result<Vec<u8>, Error>,
+    {
+        Self::import(json, None, load)
+    }
+
+    // Imports a binary .glb file. The first buffer without a URI is the
+    // binary chunk.
+    pub fn from_glb<F>(glb: &[u8], load: F) -> Result<Gltf, Error>
+    where
+        F: FnMut(&str) -> Result<Vec<u8>, Error>,
+    {
+        let u32_at = |offset: usize| -> Result<u32, Error> {
+            glb.get(offset..offset + 4)
+                .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+                .ok_or_else(|| Error::from("glb: unexpected end of file"))
+        };
+
+        if u32_at(0)? != GLB_MAGIC {
+            return Err("glb: not a binary glTF file".into());
+        }
+        if u32_at(4)? != 2 {
+            return Err(format!("glb: unsupported version {}", u32_at(4)?).into());
+        }
+        let length = (u32_at(8)? as usize).min(glb.len());
+
+        let mut json = None;
+        let mut bin = None;
+        let mut offset = 12;
+        while offset < length {
+            let chunk_length = u32_at(offset)? as usize;
+            let chunk_type = u32_at(offset + 4)?;
+            let data = glb.get(offset + 8..offset + 8 + chunk_length)
+                .ok_or_else(|| Error::from("glb: chunk exceeds the file"))?;
+            match chunk_type {
+                GLB_CHUNK_JSON if json.is_none() => json = Some(data),
+                GLB_CHUNK_BIN if bin.is_none() => bin = Some(data),
+                // Unknown chunks must be ignored.
+                _ => {}
+            }
+            // Chunks are padded to 4 bytes.
+            offset += 8 + (chunk_length + 3) / 4 * 4;
+        }
+
+        let json = json.ok_or_else(|| Error::from("glb: missing JSON chunk"))?;
+        Self::import(json, bin, load)
+    }
+
+    fn import<F>(json: &[u8], bin: Option<&[u8]>, mut load: F) -> Result<Gltf, Error>
+    where
+        F: FnMut(&str) -> Result<Vec<u8>, Error>,
+    {
+        let document: schema::Document = serde_json::from_slice(json)
+            .map_err(|e| Error::from(format!("gltf: {}", e)))?;
+        if !document.asset.version.starts_with("2.") {
+            return Err(format!("gltf: unsupported version {}", document.asset.version).into());
+        }
+        if let Some(extension) = document.extensions_required.first() {
+            return Err(format!("gltf: unsupported required extension {}", extension).into());
+        }
+        check_references(&document)?;
+
+        let mut bin = bin;
+        let buffers = document.buffers.iter().enumerate().map(|(n, buffer)| {
+            let data = match &buffer.uri {
+                Some(uri) => match decode_data_uri(uri)? {
+                    Some((_, data)) => data,
+                    None => load(uri)?,
+                },
+                None => bin.take()
+                    .ok_or_else(|| Error::from(format!("gltf: buffer {} has no data", n)))?
+                    .to_vec(),
+            };
+            if data.len() < buffer.byte_length {
+                return Err(format!("gltf: buffer {} is shorter than its byteLength", n).into());
+            }
+            Ok(data)
+        }).collect::<Result<Vec<_>, Error>>()?;
+
+        let importer = Importer { document: &document, buffers };
+        let images = document.images.iter().enumerate()
+            .map(|(n, image)| importer.image(n, image, &mut load))
+            .collect::<Result<Vec<_>, _>>()?;
+        let meshes = document.meshes.iter()
+            .map(|mesh| importer.mesh(mesh))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Gltf {
+            meshes,
+            materials: document.materials.iter().map(material).collect(),
+            textures: importer.textures()?,
+            images,
+            nodes: importer.nodes()?,
+            scene: document.scene.or(if document.scenes.is_empty() { None } else { Some(0) })
+                .map(|n| document.scenes.get(n).map(|s| s.nodes.clone())
+                    .ok_or_else(|| Error::from(format!("gltf: scene {} does not exist", n))))
+                .transpose()?
+                .unwrap_or_default(),
+        })
+    }
+}
+
+#[allow(dead_code)]
+impl GltfMesh {
+    pub fn upload(&self, gl: &GL) -> Result<Vec<Primitive>, Error> {
+        self.primitives.iter().map(|p| p.upload(gl)).collect()
+    }
+}
+
+#[allow(dead_code)]
+impl GltfPrimitive {
+    pub fn upload(&self, gl: &GL) -> Result<Primitive, Error> {
+        upload(gl, self.draw_array_mode, &self.vertices, self.indices.as_deref())
+    }
+}
+
+fn upload<V: Vertex>(gl: &GL, mode: DrawArrayMode, vertices: &[V], indices: Option<&[u32]>) -> Result<Primitive, Error> {
+    fn new<T: VertexAttributeArray>(gl: &GL, data: T, indices: Option<&[u32]>) -> Result<Primitive, Error> {
+        match indices {
+            Some(indices) => Primitive::new_indexed(gl, data, indices),
+            None => Primitive::new(gl, data),
+        }
+    }
+
+    match mode {
+        DrawArrayMode::Points => new(gl, SlicePoints(vertices), indices),
+        DrawArrayMode::Lines => new(gl, SliceLines(vertices), indices),
+        DrawArrayMode::LineStrip => new(gl, SliceLineStrip(vertices), indices),
+        DrawArrayMode::LineLoop => new(gl, SliceLineLoop(vertices), indices),
+        DrawArrayMode::TriangleStrip => new(gl, SliceTriangleStrip(vertices), indices),
+        DrawArrayMode::TriangleList => new(gl, SliceTriangleList(vertices), indices),
+        DrawArrayMode::TriangleFan => new(gl, SliceTriangleFan(vertices), indices),
+    }
+}
+
+struct Importer<'a> {
+    document: &'a schema::Document,
+    buffers: Vec<Vec<u8>>,
+}
+
+impl<'a> Importer<'a> {
+    fn buffer_view(&self, index: usize) -> Result<(&'a schema::BufferView, &[u8]), Error> {
+        let view = self.document.buffer_views.get(index)
+            .ok_or_else(|| Error::from(format!("gltf: buffer view {} does not exist", index)))?;
+        let end = view.byte_offset.checked_add(view.byte_length);
+        let data = self.buffers.get(view.buffer)
+            .zip(end)
+            .and_then(|(buffer, end)| buffer.get(view.byte_offset..end))
+            .ok_or_else(|| Error::from(format!("gltf: buffer view {} is out of range", index)))?;
+        Ok((view, data))
+    }
+
+    // Reads count elements of `kind` from a buffer view as f64, which holds
+    // every component type exactly. Normalized integers are mapped to [0, 1]
+    // or [-1, 1].
+    fn read_view(
+        &self,
+        view: usize,
+        byte_offset: usize,
+        count: usize,
+        kind: &str,
+        component_type: u32,
+        normalized: bool,
+    ) -> Result<Vec<f64>, Error> {
+        let (view_def, data) = self.buffer_view(view)?;
+        let (rows, columns) = element_shape(kind)?;
+        let size = component_size(component_type)?;
+        // Matrix columns start at 4-byte boundaries.
+        let column_stride = if columns > 1 { (rows * size + 3) / 4 * 4 } else { rows * size };
+        let element_size = columns * column_stride;
+        let stride = view_def.byte_stride.unwrap_or(element_size);
+        if stride < element_size {
+            return Err(format!("gltf: buffer view {} stride is smaller than its elements", view).into());
+        }
+
+        let end = match count {
+            0 => Some(0),
+            _ => (count - 1).checked_mul(stride)
+                .and_then(|n| n.checked_add(byte_offset))
+                .and_then(|n| n.checked_add(element_size)),
+        };
+        if end.map_or(true, |end| end > data.len()) {
+            return Err(format!("gltf: accessor exceeds buffer view {}", view).into());
+        }
+
+        let mut values = Vec::with_capacity(count * rows * columns);
+        for element in 0..count {
+            let base = byte_offset + element * stride;
+            for column in 0..columns {
+                for row in 0..rows {
+                    let at = base + column * column_stride + row * size;
+                    values.push(read_component(&data[at..at + size], component_type, normalized));
+                }
+            }
+        }
+        Ok(values)
+    }
+
+    // Components of every element of an accessor, with sparse substitutions applied.
+    fn read_accessor(&self, index: usize) -> Result<(Vec<f64>, usize), Error> {
+        let accessor = self.document.accessors.get(index)
+            .ok_or_else(|| Error::from(format!("gltf: accessor {} does not exist", index)))?;
+        let (rows, columns) = element_shape(&accessor.kind)?;
+        let components = rows * columns;
+
+        let mut values = match accessor.buffer_view {
+            Some(view) => self.read_view(
+                view, accessor.byte_offset, accessor.count,
+                &accessor.kind, accessor.component_type, accessor.normalized)?,
+            // Without a buffer view all values are zero, unless sparse says
+            // otherwise. Nothing bounds the count then, so it is capped by the
+            // size of the buffers to keep the allocation in proportion to the file.
+            None => {
+                let buffer_size: usize = self.buffers.iter().map(Vec::len).sum();
+                match accessor.count.checked_mul(components) {
+                    Some(length) if accessor.count <= buffer_size => vec![0.0; length],
+                    _ => return Err(format!("gltf: accessor {} has too many elements", index).into()),
+                }
+            }
+        };
+
+        if let Some(sparse) = &accessor.sparse {
+            let indices = self.read_view(
+                sparse.indices.buffer_view, sparse.indices.byte_offset, sparse.count,
+                "SCALAR", sparse.indices.component_type, false)?;
+            let substitutes = self.read_view(
+                sparse.values.buffer_view, sparse.values.byte_offset, sparse.count,
+                &accessor.kind, accessor.component_type, accessor.normalized)?;
+            for (n, &i) in indices.iter().enumerate() {
+                let i = i as usize;
+                if i >= accessor.count {
+                    return Err(format!("gltf: sparse index {} of accessor {} is out of range", i, index).into());
+                }
+                values[i * components..][..components]
+                    .copy_from_slice(&substitutes[n * components..][..components]);
+            }
+        }
+
+        Ok((values, components))
+    }
+
+    fn mesh(&self, mesh: &schema::Mesh) -> Result<GltfMesh, Error> {
+        Ok(GltfMesh {
+            name: mesh.name.clone(),
+            primitives: mesh.primitives.iter().map(|p| self.primitive(p)).collect::<Result<_, _>>()?,
+        })
+    }
+
+    fn primitive(&self, primitive: &schema::Primitive) -> Result<GltfPrimitive, Error> {
+        let draw_array_mode = match primitive.mode {
+            0 => DrawArrayMode::Points,
+            1 => DrawArrayMode::Lines,
+            2 => DrawArrayMode::LineLoop,
+            3 => DrawArrayMode::LineStrip,
+            4 => DrawArrayMode::TriangleList,
+            5 => DrawArrayMode::TriangleStrip,
+            6 => DrawArrayMode::TriangleFan,
+            mode => return Err(format!("gltf: unknown primitive mode {}", mode).into()),
+        };
+
+        if !primitive.attributes.contains_key("POSITION") {
+            return Err("gltf: primitive without POSITION".into());
+        }
+        // Sized by POSITION once its accessor has been read, and so checked.
+        let mut vertices = Vec::new();
+
+        // The setters index elements directly, so the accessor type is checked first.
+        let mut apply = |name: &str, kinds: &[&str], set: &dyn Fn(&mut GltfVertex, &[f32])| -> Result<bool, Error> {
+            let accessor = match primitive.attributes.get(name) {
+                Some(&accessor) => accessor,
+                None => return Ok(false),
+            };
+            let kind = &self.document.accessors.get(accessor)
+                .ok_or_else(|| Error::from(format!("gltf: accessor {} does not exist", accessor)))?
+                .kind;
+            if !kinds.contains(&kind.as_str()) {
+                return Err(format!("gltf: {} must be {}", name, kinds.join(" or ")).into());
+            }
+            let (values, components) = self.read_accessor(accessor)?;
+            if name == "POSITION" {
+                vertices = vec![GltfVertex::default(); values.len() / components];
+            } else if values.len() / components != vertices.len() {
+                return Err(format!("gltf: {} count differs from POSITION", name).into());
+            }
+            let mut element = [0.0; 16];
+            for (vertex, values) in vertices.iter_mut().zip(values.chunks(components)) {
+                for (e, &v) in element.iter_mut().zip(values) {
+                    *e = v as f32;
+                }
+                set(vertex, &element[..components]);
+            }
+            Ok(true)
+        };
+
+        apply("POSITION", &["VEC3"], &|v, e| v.position = vec3!(e[0], e[1], e[2]))?;
+        let has_normals = apply("NORMAL", &["VEC3"], &|v, e| v.normal = vec3!(e[0], e[1], e[2]))?;
+        apply("TANGENT", &["VEC4"], &|v, e| v.tangent = vec4!(e[0], e[1], e[2], e[3]))?;
+        apply("TEXCOORD_0", &["VEC2"], &|v, e| v.texture_coordination = vec2!(e[0], e[1]))?;
+        apply("COLOR_0", &["VEC3", "VEC4"], &|v, e| v.color = Color::new(e[0], e[1], e[2], e.get(3).copied().unwrap_or(1.0)))?;
+        apply("JOINTS_0", &["VEC4"], &|v, e| v.joints = [e[0] as u16, e[1] as u16, e[2] as u16, e[3] as u16])?;
+        apply("WEIGHTS_0", &["VEC4"], &|v, e| v.weights = vec4!(e[0], e[1], e[2], e[3]))?;
+
+        let mut indices = match primitive.indices {
+            Some(accessor) => {
+                let (values, _) = self.read_accessor(accessor)?;
+                if let Some(&i) = values.iter().find(|&&i| i as usize >= vertices.len()) {
+                    return Err(format!("gltf: index {} is out of range", i).into());
+                }
+                Some(values.into_iter().map(|i| i as u32).collect::<Vec<_>>())
+            }
+            None => None,
+        };
+
+        // Flat normals are required when normals are missing, so every
+        // triangle gets its own vertices.
+        if !has_normals && draw_array_mode == DrawArrayMode::TriangleList {
+            if let Some(list) = indices.take() {
+                vertices = list.iter().map(|&i| vertices[i as usize]).collect();
+            }
+            for triangle in vertices.chunks_mut(3) {
+                if let [a, b, c] = triangle {
+                    let normal = face_normal(a.position, b.position, c.position);
+                    a.normal = normal;
+                    b.normal = normal;
+                    c.normal = normal;
+                }
+            }
+        }
+
+        Ok(GltfPrimitive {
+            draw_array_mode,
+            vertices,
+            indices,
+            material: primitive.material,
+        })
+    }
+
+    fn image<F>(&self, n: usize, image: &schema::Image, load: &mut F) -> Result<GltfImage, Error>
+    where
+        F: FnMut(&str) -> Result<Vec<u8>, Error>,
+    {
+        let (mime_type, data) = match (&image.uri, image.buffer_view) {
+            (Some(uri), _) => match decode_data_uri(uri)? {
+                Some((mime_type, data)) => (image.mime_type.clone().or(Some(mime_type)), data),
+                None => (image.mime_type.clone(), load(uri)?),
+            },
+            (None, Some(view)) => (image.mime_type.clone(), self.buffer_view(view)?.1.to_vec()),
+            (None, None) => return Err(format!("gltf: image {} has no data", n).into()),
+        };
+        Ok(GltfImage { name: image.name.clone(), mime_type, data })
+    }
+
+    fn textures(&self) -> Result<Vec<GltfTexture>, Error> {
+        self.document.textures.iter().map(|texture| {
+            let sampler = match texture.sampler {
+                Some(n) => {
+                    let sampler = self.document.samplers.get(n)
+                        .ok_or_else(|| Error::from(format!("gltf: sampler {} does not exist", n)))?;
+                    GltfSampler {
+                        mag_filter: sampler.mag_filter,
+                        min_filter: sampler.min_filter,
+                        wrap_s: sampler.wrap_s,
+                        wrap_t: sampler.wrap_t,
+                    }
+                }
+                None => GltfSampler { mag_filter: None, min_filter: None, wrap_s: 10497, wrap_t: 10497 },
+            };
+            Ok(GltfTexture { image: texture.source, sampler })
+        }).collect()
+    }
+
+    // Local and world transforms of every node. Each node has at most one parent
+    // and the hierarchy must not contain cycles.
+    fn nodes(&self) -> Result<Vec<GltfNode>, Error> {
+        let defs = &self.document.nodes;
+        let mut parents: Vec<Option<usize>> = vec![None; defs.len()];
+        for (n, node) in defs.iter().enumerate() {
+            for &child in &node.children {
+                match parents.get_mut(child) {
+                    Some(parent @ None) => *parent = Some(n),
+                    Some(Some(_)) => return Err(format!("gltf: node {} has several parents", child).into()),
+                    None => return Err(format!("gltf: node {} does not exist", child).into()),
+                }
+            }
+        }
+
+        let mut nodes: Vec<GltfNode> = defs.iter().zip(&parents).map(|(node, &parent)| GltfNode {
+            name: node.name.clone(),
+            mesh: node.mesh,
+            parent,
+            children: node.children.clone(),
+            transform: local_transform(node),
+            world_transform: Matrix4::IDENT,
+        }).collect();
+
+        // Parents before children, starting from the roots.
+        let mut stack: Vec<usize> = (0..nodes.len()).filter(|&n| parents[n].is_none()).collect();
+        let mut visited = 0;
+        while let Some(n) = stack.pop() {
+            visited += 1;
+            nodes[n].world_transform = match nodes[n].parent {
+                Some(parent) => nodes[parent].world_transform * nodes[n].transform,
+                None => nodes[n].transform,
+            };
+            stack.extend(nodes[n].children.iter().copied());
+        }
+        if visited != nodes.len() {
+            return Err("gltf: node hierarchy contains a cycle".into());
+        }
+        Ok(nodes)
+    }
+}
+
+fn check_index(index: Option<usize>, count: usize, what: &str) -> Result<(), Error> {
+    match index {
+        Some(index) if index >= count => Err(format!("gltf: {} {} does not exist", what, index).into()),
+        _ => Ok(()),
+    }
+}
+
+// Indices that are passed on to the imported asset must refer to something.
+// Accessors, buffer views and samplers are checked where they are read.
+fn check_references(document: &schema::Document) -> Result<(), Error> {
+    for node in &document.nodes {
+        check_index(node.mesh, document.meshes.len(), "mesh")?;
+    }
+    for primitive in document.meshes.iter().flat_map(|mesh| &mesh.primitives) {
+        check_index(primitive.material, document.materials.len(), "material")?;
+    }
+    for texture in &document.textures {
+        check_index(texture.source, document.images.len(), "image")?;
+    }
+    for material in &document.materials {
+        let pbr = material.pbr_metallic_roughness.as_ref();
+        let infos = [
+            pbr.and_then(|pbr| pbr.base_color_texture.as_ref()),
+            pbr.and_then(|pbr| pbr.metallic_roughness_texture.as_ref()),
+            material.normal_texture.as_ref(),
+            material.occlusion_texture.as_ref(),
+            material.emissive_texture.as_ref(),
+        ];
+        for info in infos.iter().flatten() {
+            check_index(Some(info.index), document.textures.len(), "texture")?;
+        }
+    }
+    for scene in &document.scenes {
+        for &node in &scene.nodes {
+            check_index(Some(node), document.nodes.len(), "node")?;
+        }
+    }
+    Ok(())
+}
+
+fn local_transform(node: &schema::Node) -> Matrix4 {
+    if let Some(m) = node.matrix {
+        return mat4!(
+            m[0], m[4], m[8], m[12],
+            m[1], m[5], m[9], m[13],
+            m[2], m[6], m[10], m[14],
+            m[3], m[7], m[11], m[15]
+        );
+    }
+    let [tx, ty, tz] = node.translation.unwrap_or([0.0; 3]);
+    let [x, y, z, w] = node.rotation.unwrap_or([0.0, 0.0, 0.0, 1.0]);
+    let [sx, sy, sz] = node.scale.unwrap_or([1.0; 3]);
+    Matrix4::compose(vec3!(tx, ty, tz), Quaternion(vec4!(x, y, z, w)), vec3!(sx, sy, sz))
+}
+
+fn material(material: &schema::Material) -> GltfMaterial {
+    let texture = |info: &Option<schema::TextureInfo>| info.as_ref().map(|info| GltfTextureRef {
+        texture: info.index,
+        texture_coordination: info.tex_coord,
+    });
+    let pbr = material.pbr_metallic_roughness.as_ref();
+    let [r, g, b, a] = pbr.map_or([1.0; 4], |pbr| pbr.base_color_factor);
+    let [er, eg, eb] = material.emissive_factor;
+
+    GltfMaterial {
+        name: material.name.clone(),
+        base_color: Color::new(r, g, b, a),
+        base_color_texture: pbr.and_then(|pbr| texture(&pbr.base_color_texture)),
+        metallic: pbr.map_or(1.0, |pbr| pbr.metallic_factor),
+        roughness: pbr.map_or(1.0, |pbr| pbr.roughness_factor),
+        metallic_roughness_texture: pbr.and_then(|pbr| texture(&pbr.metallic_roughness_texture)),
+        normal_texture: texture(&material.normal_texture),
+        normal_scale: material.normal_texture.as_ref().map_or(1.0, |info| info.scale),
+        occlusion_texture: texture(&material.occlusion_texture),
+        occlusion_strength: material.occlusion_texture.as_ref().map_or(1.0, |info| info.strength),
+        emissive: vec3!(er, eg, eb),
+        emissive_texture: texture(&material.emissive_texture),
+        alpha_mode: match material.alpha_mode.as_str() {
+            "MASK" => AlphaMode::Mask(material.alpha_cutoff),
+            "BLEND" => AlphaMode::Blend,
+            _ => AlphaMode::Opaque,
+        },
+        double_sided: material.double_sided,
+    }
+}
+
+// (rows, columns) of an accessor type.
+fn element_shape(kind: &str) -> Result<(usize, usize), Error> {
+    match kind {
+        "SCALAR" => Ok((1, 1)),
+        "VEC2" => Ok((2, 1)),
+        "VEC3" => Ok((3, 1)),
+        "VEC4" => Ok((4, 1)),
+        "MAT2" => Ok((2, 2)),
+        "MAT3" => Ok((3, 3)),
+        "MAT4" => Ok((4, 4)),
+        _ => Err(format!("gltf: unknown accessor type {}", kind).into()),
+    }
+}
+
+fn component_size(component_type: u32) -> Result<usize, Error> {
+    match component_type {
+        BYTE | UNSIGNED_BYTE => Ok(1),
+        SHORT | UNSIGNED_SHORT => Ok(2),
+        UNSIGNED_INT | FLOAT => Ok(4),
+        _ => Err(format!("gltf: unknown component type {}", component_type).into()),
+    }
+}
+
+// Little endian. The size of `bytes` matches the component type.
+fn read_component(bytes: &[u8], component_type: u32, normalized: bool) -> f64 {
+    let value = match component_type {
+        BYTE => bytes[0] as i8 as f64,
+        UNSIGNED_BYTE => bytes[0] as f64,
+        SHORT => i16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+        UNSIGNED_SHORT => u16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+        UNSIGNED_INT => u32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+        _ => f32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+    };
+    if !normalized {
+        return value;
+    }
+    match component_type {
+        BYTE => (value / 127.0).max(-1.0),
+        UNSIGNED_BYTE => value / 255.0,
+        SHORT => (value / 32767.0).max(-1.0),
+        UNSIGNED_SHORT => value / 65535.0,
+        UNSIGNED_INT => value / u32::MAX as f64,
+        _ => value,
+    }
+}
+
+// Decodes `data:[<mime type>][;base64],<data>`. None if uri is not a data URI.
+fn decode_data_uri(uri: &str) -> Result<Option<(String, Vec<u8>)>, Error> {
+    let rest = match uri.strip_prefix("data:") {
+        Some(rest) => rest,
+        None => return Ok(None),
+    };
+    let (header, data) = rest.split_once(',')
+        .ok_or_else(|| Error::from("gltf: malformed data URI"))?;
+    let mime_type = header.strip_suffix(";base64")
+        .ok_or_else(|| Error::from("gltf: only base64 data URIs are supported"))?;
+    Ok(Some((mime_type.to_string(), decode_base64(data)?)))
+}
+
+fn decode_base64(data: &str) -> Result<Vec<u8>, Error> {
+    let sextet = |c: u8| -> Result<u32, Error> {
+        match c {
+            b'A'..=b'Z' => Ok((c - b'A') as u32),
+            b'a'..=b'z' => Ok((c - b'a' + 26) as u32),
+            b'0'..=b'9' => Ok((c - b'0' + 52) as u32),
+            b'+' | b'-' => Ok(62),
+            b'/' | b'_' => Ok(63),
+            _ => Err(format!("base64: invalid character {:?}", c as char).into()),
+        }
+    };
+
+    let data = data.trim_end_matches('=').as_bytes();
+    let mut bytes = Vec::with_capacity(data.len() * 3 / 4);
+    for chunk in data.chunks(4) {
+        if chunk.len() == 1 {
+            return Err("base64: truncated data".into());
+        }
+        let mut bits = 0;
+        for (n, &c) in chunk.iter().enumerate() {
+            bits |= sextet(c)? << (18 - 6 * n);
+        }
+        let decoded = [(bits >> 16) as u8, (bits >> 8) as u8, bits as u8];
+        bytes.extend_from_slice(&decoded[..chunk.len() - 1]);
+    }
+    Ok(bytes)
+}
@@ -0,0 +1,478 @@
+use std::collections::HashMap;
+use std::f32::consts::PI;
+
+use crate::error::Error;
+use crate::math::{Vector2, Vector3, Vector4};
+use crate::{vec2, vec3, vec4};
+
+use super::{optimize, Primitive, SliceTriangleList, SliceTriangleStrip, Vertex, GL};
+
+#[derive(Debug, Copy, Clone, PartialEq, Vertex)]
+#[repr(C)]
+pub struct MeshVertex {
+    pub position: Vector3,
+    pub normal: Vector3,
+    #[vertex(name = "textureCoord")]
+    pub texture_coordination: Vector2,
+}
+
+// MeshVertex with a tangent for normal mapping. xyz is the direction of
+// increasing u and w the handedness: bitangent = w * cross(normal, tangent.xyz).
+#[derive(Debug, Copy, Clone, PartialEq, Vertex)]
+#[repr(C)]
+pub struct TangentVertex {
+    pub position: Vector3,
+    pub normal: Vector3,
+    pub tangent: Vector4,
+    #[vertex(name = "textureCoord")]
+    pub texture_coordination: Vector2,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TangentMesh {
+    pub vertices: Vec<TangentVertex>,
+    pub indices: Vec<u32>,
+}
+
+#[allow(dead_code)]
+impl TangentMesh {
+    pub fn upload(&self, gl: &GL) -> Result<Primitive, Error> {
+        Primitive::new_indexed(gl, SliceTriangleList(&self.vertices), &self.indices)
+    }
+}
+
+// Indexed triangle list. Triangles are counter-clockwise seen from the outside
+// and every shape is centered at the origin with Y up.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Mesh {
+    pub vertices: Vec<MeshVertex>,
+    pub indices: Vec<u32>,
+}
+
+#[allow(dead_code)]
+impl Mesh {
+    pub fn new() -> Mesh {
+        Mesh::default()
+    }
+
+    pub fn upload(&self, gl: &GL) -> Result<Primitive, Error> {
+        Primitive::new_indexed(gl, SliceTriangleList(&self.vertices), &self.indices)
+    }
+
+    // Uploads the mesh as a single triangle strip. Usually smaller than the
+    // list for meshes optimized with `optimize`.
+    pub fn upload_strip(&self, gl: &GL) -> Result<Primitive, Error> {
+        Primitive::new_indexed(gl, SliceTriangleStrip(&self.vertices), &optimize::stripify(&self.indices))
+    }
+
+    // Welds duplicate vertices, then orders triangles for the vertex cache and
+    // vertices by first use. Draws the same triangles.
+    pub fn optimize(&mut self) {
+        let (vertices, indices) = optimize::weld(&self.vertices, &self.indices);
+        let indices = optimize::optimize_vertex_cache(&indices);
+        let (vertices, indices) = optimize::optimize_vertex_fetch(&vertices, &indices);
+        self.vertices = vertices;
+        self.indices = indices;
+    }
+
+    // Number of triangles.
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    fn push_vertex(&mut self, position: Vector3, normal: Vector3, texture_coordination: Vector2) -> u32 {
+        self.vertices.push(MeshVertex { position, normal, texture_coordination });
+        (self.vertices.len() - 1) as u32
+    }
+
+    // Adds a grid of (columns + 1) x (rows + 1) vertices sampled from a surface
+    // over u, v in [0, 1], returning (position, normal). The triangles face the
+    // side dP/du x dP/dv points to. Texture coordinates are (u, 1 - v).
+    fn push_surface<F>(&mut self, columns: u32, rows: u32, surface: F)
+    where
+        F: Fn(f32, f32) -> (Vector3, Vector3),
+    {
+        let first = self.vertices.len() as u32;
+        for row in 0..=rows {
+            let v = row as f32 / rows as f32;
+            for column in 0..=columns {
+                let u = column as f32 / columns as f32;
+                let (position, normal) = surface(u, v);
+                self.push_vertex(position, normal, vec2!(u, 1.0 - v));
+            }
+        }
+
+        let index = |column: u32, row: u32| first + row * (columns + 1) + column;
+        for row in 0..rows {
+            for column in 0..columns {
+                let (a, b) = (index(column, row), index(column + 1, row));
+                let (c, d) = (index(column + 1, row + 1), index(column, row + 1));
+                self.indices.extend_from_slice(&[a, b, c, a, c, d]);
+            }
+        }
+    }
+
+    // Adds a flat disk in the XZ plane at height y, facing +Y or -Y.
+    fn push_disk(&mut self, radius: f32, y: f32, up: bool, segments: u32) {
+        let normal = vec3!(0.0, if up { 1.0 } else { -1.0 }, 0.0);
+        let center = self.push_vertex(vec3!(0.0, y, 0.0), normal, vec2!(0.5, 0.5));
+        for segment in 0..=segments {
+            let (sin, cos) = turn(segment as f32 / segments as f32);
+            self.push_vertex(
+                vec3!(radius * sin, y, radius * cos),
+                normal,
+                vec2!(0.5 + 0.5 * sin, 0.5 - 0.5 * cos));
+        }
+        for segment in 0..segments {
+            let (a, b) = (center + 1 + segment, center + 2 + segment);
+            if up {
+                self.indices.extend_from_slice(&[center, a, b]);
+            } else {
+                self.indices.extend_from_slice(&[center, b, a]);
+            }
+        }
+    }
+
+    // Axis-aligned cube with edges of length size. Each face has its own
+    // vertices so normals and texture coordinates are per face.
+    pub fn cube(size: f32) -> Mesh {
+        // (normal, du, dv) with du x dv = normal.
+        let faces = [
+            (vec3!(1.0, 0.0, 0.0), vec3!(0.0, 0.0, -1.0), vec3!(0.0, 1.0, 0.0)),
+            (vec3!(-1.0, 0.0, 0.0), vec3!(0.0, 0.0, 1.0), vec3!(0.0, 1.0, 0.0)),
+            (vec3!(0.0, 1.0, 0.0), vec3!(1.0, 0.0, 0.0), vec3!(0.0, 0.0, -1.0)),
+            (vec3!(0.0, -1.0, 0.0), vec3!(1.0, 0.0, 0.0), vec3!(0.0, 0.0, 1.0)),
+            (vec3!(0.0, 0.0, 1.0), vec3!(1.0, 0.0, 0.0), vec3!(0.0, 1.0, 0.0)),
+            (vec3!(0.0, 0.0, -1.0), vec3!(-1.0, 0.0, 0.0), vec3!(0.0, 1.0, 0.0)),
+        ];
+
+        let mut mesh = Mesh::new();
+        for (normal, du, dv) in faces {
+            mesh.push_surface(1, 1, |u, v| {
+                let position = (normal * 0.5 + du * (u - 0.5) + dv * (v - 0.5)) * size;
+                (position, normal)
+            });
+        }
+        mesh
+    }
+
+    // Sphere made of segments around Y and rings from pole to pole.
+    // The poles and the seam at u = 0 and 1 have duplicated vertices.
+    pub fn uv_sphere(radius: f32, segments: u32, rings: u32) -> Mesh {
+        let mut mesh = Mesh::new();
+        mesh.push_surface(segments, rings, |u, v| {
+            let (sin_phi, cos_phi) = turn(u);
+            let (sin_theta, cos_theta) = (v * PI).sin_cos();
+            let normal = vec3!(sin_theta * sin_phi, -cos_theta, sin_theta * cos_phi);
+            (normal * radius, normal)
+        });
+        mesh
+    }
+
+    // Sphere made by subdividing an icosahedron; triangles are nearly uniform.
+    // Each subdivision quadruples the 20 faces. Texture coordinates are
+    // spherical. Triangles crossing the seam at u = 0/1 get their own vertices
+    // with u past 1, so textures need REPEAT wrapping there, and each triangle
+    // at a pole gets its own pole vertex with u at the triangle's center.
+    pub fn icosphere(radius: f32, subdivisions: u32) -> Mesh {
+        let t = (1.0 + 5.0f32.sqrt()) / 2.0;
+        let mut positions: Vec<Vector3> = [
+            (-1.0, t, 0.0), (1.0, t, 0.0), (-1.0, -t, 0.0), (1.0, -t, 0.0),
+            (0.0, -1.0, t), (0.0, 1.0, t), (0.0, -1.0, -t), (0.0, 1.0, -t),
+            (t, 0.0, -1.0), (t, 0.0, 1.0), (-t, 0.0, -1.0), (-t, 0.0, 1.0),
+        ].iter().map(|&(x, y, z)| vec3!(x, y, z).normalize()).collect();
+
+        #[rustfmt::skip]
+        let mut triangles: Vec<[u32; 3]> = vec![
+            [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+            [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+            [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+            [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+        ];
+
+        for _ in 0..subdivisions {
+            let mut midpoints = HashMap::new();
+            let mut midpoint = |a: u32, b: u32| {
+                *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                    let p = (positions[a as usize] + positions[b as usize]).normalize();
+                    positions.push(p);
+                    (positions.len() - 1) as u32
+                })
+            };
+            triangles = triangles.iter().flat_map(|&[a, b, c]| {
+                let (ab, bc, ca) = (midpoint(a, b), midpoint(b, c), midpoint(c, a));
+                [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+            }).collect();
+        }
+
+        let u = |n: Vector3| 0.5 + n.x.atan2(n.z) / (2.0 * PI);
+        let v = |n: Vector3| n.y.clamp(-1.0, 1.0).acos() / PI;
+        let is_pole = |n: Vector3| n.x.abs() < 1e-6 && n.z.abs() < 1e-6;
+
+        let mut mesh = Mesh::new();
+        // Vertices by position and whether u is shifted past the seam.
+        let mut shared: HashMap<(u32, bool), u32> = HashMap::new();
+        for triangle in triangles {
+            let normals = triangle.map(|i| positions[i as usize]);
+            let us = normals.map(|n| if is_pole(n) { None } else { Some(u(n)) });
+            let max_u = us.iter().flatten().fold(0.0f32, |a, &b| a.max(b));
+            // (u, shifted past the seam)
+            let us = us.map(|u| u.map(|u| if max_u - u > 0.5 { (u + 1.0, true) } else { (u, false) }));
+            let others = us.iter().flatten();
+            let center_u = others.clone().map(|&(u, _)| u).sum::<f32>() / others.count() as f32;
+
+            for ((&i, &normal), u) in triangle.iter().zip(&normals).zip(us) {
+                let index = match u {
+                    Some((u, shifted)) => *shared.entry((i, shifted)).or_insert_with(|| {
+                        mesh.push_vertex(normal * radius, normal, vec2!(u, v(normal)))
+                    }),
+                    None => mesh.push_vertex(normal * radius, normal, vec2!(center_u, v(normal))),
+                };
+                mesh.indices.push(index);
+            }
+        }
+        mesh
+    }
+
+    // Flat rectangle in the XZ plane facing +Y.
+    pub fn plane(width: f32, depth: f32) -> Mesh {
+        Mesh::grid(width, depth, 1, 1)
+    }
+
+    // Plane subdivided into columns along X and rows along Z.
+    pub fn grid(width: f32, depth: f32, columns: u32, rows: u32) -> Mesh {
+        let mut mesh = Mesh::new();
+        mesh.push_surface(columns, rows, |u, v| {
+            let position = vec3!((u - 0.5) * width, 0.0, (0.5 - v) * depth);
+            (position, vec3!(0.0, 1.0, 0.0))
+        });
+        mesh
+    }
+
+    // Closed cylinder along Y.
+    pub fn cylinder(radius: f32, height: f32, segments: u32) -> Mesh {
+        let mut mesh = Mesh::new();
+        mesh.push_surface(segments, 1, |u, v| {
+            let (sin, cos) = turn(u);
+            let normal = vec3!(sin, 0.0, cos);
+            (vec3!(radius * sin, (v - 0.5) * height, radius * cos), normal)
+        });
+        mesh.push_disk(radius, 0.5 * height, true, segments);
+        mesh.push_disk(radius, -0.5 * height, false, segments);
+        mesh
+    }
+
+    // Closed cone along Y with the apex at the top.
+    pub fn cone(radius: f32, height: f32, segments: u32) -> Mesh {
+        let mut mesh = Mesh::new();
+        mesh.push_surface(segments, 1, |u, v| {
+            let (sin, cos) = turn(u);
+            let normal = vec3!(height * sin, radius, height * cos).normalize();
+            let r = (1.0 - v) * radius;
+            (vec3!(r * sin, (v - 0.5) * height, r * cos), normal)
+        });
+        mesh.push_disk(radius, -0.5 * height, false, segments);
+        mesh
+    }
+
+    // Torus around Y. major_radius is from the center to the middle of the
+    // tube, minor_radius the radius of the tube.
+    pub fn torus(major_radius: f32, minor_radius: f32, major_segments: u32, minor_segments: u32) -> Mesh {
+        let mut mesh = Mesh::new();
+        mesh.push_surface(major_segments, minor_segments, |u, v| {
+            let (sin_phi, cos_phi) = turn(u);
+            let (sin_psi, cos_psi) = turn(v);
+            let normal = vec3!(cos_psi * sin_phi, sin_psi, cos_psi * cos_phi);
+            let center = vec3!(major_radius * sin_phi, 0.0, major_radius * cos_phi);
+            (center + normal * minor_radius, normal)
+        });
+        mesh
+    }
+
+    // Cylinder along Y with hemispherical ends. height is the length of the
+    // cylindrical part; the total height is height + 2 * radius.
+    pub fn capsule(radius: f32, height: f32, segments: u32, rings: u32) -> Mesh {
+        // rings per hemisphere and one row for the cylinder in between.
+        let rows = 2 * rings + 1;
+        let mut mesh = Mesh::new();
+        mesh.push_surface(segments, rows, |u, v| {
+            let row = (v * rows as f32).round() as u32;
+            let (theta, center) = if row <= rings {
+                (row as f32 / rings as f32 * 0.5 * PI, -0.5 * height)
+            } else {
+                ((row - 1) as f32 / rings as f32 * 0.5 * PI, 0.5 * height)
+            };
+            let (sin_phi, cos_phi) = turn(u);
+            let (sin_theta, cos_theta) = theta.sin_cos();
+            let normal = vec3!(sin_theta * sin_phi, -cos_theta, sin_theta * cos_phi);
+            (normal * radius + vec3!(0.0, center, 0.0), normal)
+        });
+        mesh
+    }
+
+    // Replaces the normals with face normals. Every triangle gets its own
+    // three vertices.
+    pub fn compute_flat_normals(&mut self) {
+        self.compute_normals(0.0);
+    }
+
+    // Replaces the normals with the average of the surrounding face normals.
+    // Vertices at the same position are smoothed together even when they are
+    // split, e.g. at texture seams.
+    pub fn compute_smooth_normals(&mut self) {
+        self.compute_normals(PI);
+    }
+
+    // Replaces the normals, smoothing across edges whose faces meet at less than
+    // crease_angle (radians) and keeping sharper edges hard. Vertices are split
+    // where one vertex needs several normals. Faces are weighted by their corner
+    // angle, so the result does not depend on how the surface is triangulated.
+    pub fn compute_normals(&mut self, crease_angle: f32) {
+        let triangles: Vec<[u32; 3]> = self.indices.chunks_exact(3).map(|t| [t[0], t[1], t[2]]).collect();
+        let face_normals: Vec<Vector3> = triangles.iter().map(|t| {
+            let [a, b, c] = t.map(|i| self.vertices[i as usize].position);
+            face_normal(a, b, c)
+        }).collect();
+
+        // Corners (triangle, n) grouped by the position of their vertex.
+        let mut corners_at: HashMap<[u32; 3], Vec<(usize, usize)>> = HashMap::new();
+        for (triangle, t) in triangles.iter().enumerate() {
+            for (n, &i) in t.iter().enumerate() {
+                corners_at.entry(position_key(self.vertices[i as usize].position))
+                    .or_default()
+                    .push((triangle, n));
+            }
+        }
+
+        let cos_crease = crease_angle.min(PI).cos();
+        let corner_angle = |triangle: usize, n: usize| {
+            let t = triangles[triangle];
+            let p = |k: usize| self.vertices[t[(n + k) % 3] as usize].position;
+            corner_angle(p(0), p(1), p(2))
+        };
+
+        let mut vertices = Vec::with_capacity(self.vertices.len());
+        let mut indices = Vec::with_capacity(self.indices.len());
+        let mut split: HashMap<(u32, [u32; 3]), u32> = HashMap::new();
+        for (triangle, t) in triangles.iter().enumerate() {
+            for &i in t {
+                let vertex = self.vertices[i as usize];
+                let face_normal = face_normals[triangle];
+                let normal = if crease_angle <= 0.0 {
+                    face_normal
+                } else {
+                    let sum = corners_at[&position_key(vertex.position)].iter()
+                        .filter(|&&(other, _)| other == triangle || face_normal.dot(face_normals[other]) >= cos_crease)
+                        .fold(vec3!(0.0, 0.0, 0.0), |sum, &(other, n)| sum + face_normals[other] * corner_angle(other, n));
+                    safe_normalize(sum)
+                };
+
+                // Flat normals never share vertices between triangles.
+                let key = if crease_angle <= 0.0 { (vertices.len() as u32, [0; 3]) } else { (i, position_key(normal)) };
+                let index = *split.entry(key).or_insert_with(|| {
+                    vertices.push(MeshVertex { normal, ..vertex });
+                    (vertices.len() - 1) as u32
+                });
+                indices.push(index);
+            }
+        }
+
+        self.vertices = vertices;
+        self.indices = indices;
+    }
+
+    // Computes per-vertex tangents from positions, normals and texture
+    // coordinates, following the MikkTSpace conventions: per-face tangents are
+    // projected onto the normal plane and averaged weighted by corner angle, and
+    // w holds the handedness. Vertices shared by faces with mirrored texture
+    // coordinates are split so each side keeps its own handedness.
+    pub fn compute_tangents(&self) -> TangentMesh {
+        let triangles: Vec<[u32; 3]> = self.indices.chunks_exact(3).map(|t| [t[0], t[1], t[2]]).collect();
+
+        // Per vertex and handedness: accumulated (tangent, bitangent).
+        let mut sums: HashMap<(u32, bool), (Vector3, Vector3)> = HashMap::new();
+        let mut corner_keys = Vec::with_capacity(self.indices.len());
+        for t in &triangles {
+            let [v0, v1, v2] = t.map(|i| self.vertices[i as usize]);
+            let (e1, e2) = (v1.position - v0.position, v2.position - v0.position);
+            let (d1, d2) = (v1.texture_coordination - v0.texture_coordination, v2.texture_coordination - v0.texture_coordination);
+            let det = d1.x * d2.y - d2.x * d1.y;
+            let (tangent, bitangent) = if det.abs() > f32::EPSILON {
+                ((e1 * d2.y - e2 * d1.y) / det, (e2 * d1.x - e1 * d2.x) / det)
+            } else {
+                (vec3!(0.0, 0.0, 0.0), vec3!(0.0, 0.0, 0.0))
+            };
+            let face_normal = e1.cross(e2);
+            let right_handed = face_normal.cross(tangent).dot(bitangent) >= 0.0;
+
+            for (n, &i) in t.iter().enumerate() {
+                let vertex = self.vertices[i as usize];
+                let p = |k: usize| self.vertices[t[(n + k) % 3] as usize].position;
+                let angle = corner_angle(p(0), p(1), p(2));
+
+                let project = |v: Vector3| safe_normalize(v - vertex.normal * vertex.normal.dot(v));
+                let sum = sums.entry((i, right_handed)).or_insert((vec3!(0.0, 0.0, 0.0), vec3!(0.0, 0.0, 0.0)));
+                sum.0 = sum.0 + project(tangent) * angle;
+                sum.1 = sum.1 + project(bitangent) * angle;
+                corner_keys.push((i, right_handed));
+            }
+        }
+
+        let mut vertices = Vec::with_capacity(self.vertices.len());
+        let mut indices = Vec::with_capacity(self.indices.len());
+        let mut split: HashMap<(u32, bool), u32> = HashMap::new();
+        for key in corner_keys {
+            let index = *split.entry(key).or_insert_with(|| {
+                let MeshVertex { position, normal, texture_coordination } = self.vertices[key.0 as usize];
+                let (tangent, bitangent) = sums[&key];
+                let tangent = if tangent.dot(tangent) > f32::EPSILON {
+                    safe_normalize(tangent - normal * normal.dot(tangent))
+                } else {
+                    any_perpendicular(normal)
+                };
+                let w = if normal.cross(tangent).dot(bitangent) < 0.0 { -1.0 } else { 1.0 };
+                let vec3!(tx, ty, tz) = tangent;
+                vertices.push(TangentVertex { position, normal, tangent: vec4!(tx, ty, tz, w), texture_coordination });
+                (vertices.len() - 1) as u32
+            });
+            indices.push(index);
+        }
+
+        TangentMesh { vertices, indices }
+    }
+}
+
+// (sin, cos) of a fraction of a full turn. A full turn lands exactly on zero,
+// so vertices on both sides of a seam get identical positions.
+fn turn(fraction: f32) -> (f32, f32) {
+    (fraction % 1.0 * 2.0 * PI).sin_cos()
+}
+
+// Zero stays zero instead of becoming NaN.
+pub(super) fn safe_normalize(v: Vector3) -> Vector3 {
+    let norm = v.norm();
+    if norm > 0.0 { v / norm } else { v }
+}
+
+// Unit normal of the counter-clockwise triangle (a, b, c); zero when degenerate.
+pub(super) fn face_normal(a: Vector3, b: Vector3, c: Vector3) -> Vector3 {
+    safe_normalize((b - a).cross(c - a))
+}
+
+// Angle at corner a of the triangle (a, b, c), in radians. Used to weight face
+// normals so that the result does not depend on the triangulation.
+pub(super) fn corner_angle(a: Vector3, b: Vector3, c: Vector3) -> f32 {
+    let (e1, e2) = (safe_normalize(b - a), safe_normalize(c - a));
+    e1.dot(e2).clamp(-1.0, 1.0).acos()
+}
+
+// Some unit vector perpendicular to the unit vector n.
+fn any_perpendicular(n: Vector3) -> Vector3 {
+    let axis = if n.x.abs() < 0.9 { vec3!(1.0, 0.0, 0.0) } else { vec3!(0.0, 1.0, 0.0) };
+    safe_normalize(axis - n * n.dot(axis))
+}
+
+// Hashable identity of a position. Adding 0.0 folds -0.0 into 0.0.
+fn position_key(p: Vector3) -> [u32; 3] {
+    [p.x + 0.0, p.y + 0.0, p.z + 0.0].map(f32::to_bits)
+}
@@ -0,0 +1,323 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::error::Error;
+use crate::math::{Color, Vector2, Vector3};
+use crate::{vec2, vec3};
+
+use super::mesh::{corner_angle, face_normal, safe_normalize, Mesh, MeshVertex};
+
+// Geometry of a Wavefront OBJ file.
+//
+// Faces are triangulated as fans, so polygons are expected to be convex.
+// Texture coordinates are flipped to (u, 1 - v) to match the top-left origin
+// of textures uploaded from images. Face corners without a normal get smooth
+// normals computed from the faces around them; see `fill_missing_normals`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ObjModel {
+    pub mesh: Mesh,
+    // Ranges of mesh.indices in file order. A new group starts at every
+    // `o`, `g` or `usemtl` statement that changes the name or material.
+    pub groups: Vec<ObjGroup>,
+    // File names given to `mtllib`.
+    pub material_libraries: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ObjGroup {
+    pub name: String,
+    pub material: Option<String>,
+    pub first_index: usize,
+    pub index_count: usize,
+}
+
+// A material of a Wavefront MTL file. Texture paths are as written in the file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Material {
+    pub name: String,
+    pub ambient: Color,
+    pub diffuse: Color,
+    pub specular: Color,
+    pub emissive: Color,
+    // Specular exponent (Ns).
+    pub shininess: f32,
+    // 1 is opaque (d, or 1 - Tr).
+    pub opacity: f32,
+    pub illumination: u32,
+    pub ambient_texture: Option<String>,
+    pub diffuse_texture: Option<String>,
+    pub specular_texture: Option<String>,
+    pub opacity_texture: Option<String>,
+    pub normal_texture: Option<String>,
+}
+
+impl Material {
+    pub fn new(name: &str) -> Material {
+        Material {
+            name: name.to_string(),
+            ambient: Color::BLACK,
+            diffuse: Color::WHITE,
+            specular: Color::BLACK,
+            emissive: Color::BLACK,
+            shininess: 0.0,
+            opacity: 1.0,
+            illumination: 0,
+            ambient_texture: None,
+            diffuse_texture: None,
+            specular_texture: None,
+            opacity_texture: None,
+            normal_texture: None,
+        }
+    }
+}
+
+fn error(line: usize, message: impl std::fmt::Display) -> Error {
+    Error::from(format!("line {}: {}", line, message))
+}
+
+// Lines with comments removed and `\` continuations joined, paired with the
+// number of the line they start on. Empty lines are skipped.
+fn statements(source: &str) -> impl Iterator<Item = (usize, String)> + '_ {
+    let mut lines = source.lines().enumerate();
+    std::iter::from_fn(move || {
+        loop {
+            let (n, line) = lines.next()?;
+            let mut statement = String::new();
+            let mut line = line;
+            loop {
+                let content = line.split('#').next().unwrap_or("");
+                match content.trim_end().strip_suffix('\\') {
+                    Some(continued) => {
+                        statement.push_str(continued);
+                        statement.push(' ');
+                        match lines.next() {
+                            Some((_, next)) => line = next,
+                            None => break,
+                        }
+                    }
+                    None => {
+                        statement.push_str(content);
+                        break;
+                    }
+                }
+            }
+            let statement = statement.trim();
+            if !statement.is_empty() {
+                return Some((n + 1, statement.to_string()));
+            }
+        }
+    })
+}
+
+fn parse_number<T: FromStr>(line: usize, token: Option<&str>, what: &str) -> Result<T, Error> {
+    let token = token.ok_or_else(|| error(line, format!("missing {}", what)))?;
+    token.parse().map_err(|_| error(line, format!("invalid {}: {}", what, token)))
+}
+
+fn parse_color<'a>(line: usize, mut tokens: impl Iterator<Item = &'a str>) -> Result<Color, Error> {
+    let r = parse_number(line, tokens.next(), "color")?;
+    // A single value means gray.
+    match tokens.next() {
+        None => Ok(Color::new(r, r, r, 1.0)),
+        g => {
+            let g = parse_number(line, g, "color")?;
+            let b = parse_number(line, tokens.next(), "color")?;
+            Ok(Color::new(r, g, b, 1.0))
+        }
+    }
+}
+
+// Resolves a 1-based (or negative, relative to the end) OBJ index.
+fn resolve_index(line: usize, token: &str, count: usize, what: &str) -> Result<usize, Error> {
+    let index: i64 = token.parse().map_err(|_| error(line, format!("invalid {} index: {}", what, token)))?;
+    let resolved = if index > 0 {
+        index - 1
+    } else {
+        count as i64 + index
+    };
+    if index == 0 || resolved < 0 || resolved >= count as i64 {
+        return Err(error(line, format!("{} index {} is out of range", what, index)));
+    }
+    Ok(resolved as usize)
+}
+
+#[allow(dead_code)]
+pub fn parse_obj(source: &str) -> Result<ObjModel, Error> {
+    let mut positions: Vec<Vector3> = Vec::new();
+    let mut normals: Vec<Vector3> = Vec::new();
+    let mut texture_coordinations: Vec<Vector2> = Vec::new();
+
+    let mut model = ObjModel::default();
+    let mut vertex_indices: HashMap<(usize, Option<usize>, Option<usize>), u32> = HashMap::new();
+    // OBJ position index of each vertex and whether it came with a normal.
+    let mut normal_sources: Vec<(usize, bool)> = Vec::new();
+    let mut group = ObjGroup::default();
+
+    for (line, statement) in statements(source) {
+        let mut tokens = statement.split_whitespace();
+        let keyword = tokens.next().unwrap_or("");
+        match keyword {
+            "v" => {
+                let x = parse_number(line, tokens.next(), "x")?;
+                let y = parse_number(line, tokens.next(), "y")?;
+                let z = parse_number(line, tokens.next(), "z")?;
+                positions.push(vec3!(x, y, z));
+            }
+            "vn" => {
+                let x = parse_number(line, tokens.next(), "x")?;
+                let y = parse_number(line, tokens.next(), "y")?;
+                let z = parse_number(line, tokens.next(), "z")?;
+                normals.push(vec3!(x, y, z));
+            }
+            "vt" => {
+                let u: f32 = parse_number(line, tokens.next(), "u")?;
+                let v: f32 = match tokens.next() {
+                    None => 0.0,
+                    v => parse_number(line, v, "v")?,
+                };
+                texture_coordinations.push(vec2!(u, 1.0 - v));
+            }
+            "f" => {
+                let mut corners = Vec::new();
+                for token in tokens {
+                    let mut parts = token.split('/');
+                    let position = resolve_index(line, parts.next().unwrap_or(""), positions.len(), "position")?;
+                    let texture_coordination = match parts.next() {
+                        None | Some("") => None,
+                        Some(t) => Some(resolve_index(line, t, texture_coordinations.len(), "texture coordinate")?),
+                    };
+                    let normal = match parts.next() {
+                        None | Some("") => None,
+                        Some(n) => Some(resolve_index(line, n, normals.len(), "normal")?),
+                    };
+                    if parts.next().is_some() {
+                        return Err(error(line, format!("invalid face vertex: {}", token)));
+                    }
+
+                    let key = (position, texture_coordination, normal);
+                    let vertices = &mut model.mesh.vertices;
+                    let index = *vertex_indices.entry(key).or_insert_with(|| {
+                        vertices.push(MeshVertex {
+                            position: positions[position],
+                            normal: normal.map_or(vec3!(0.0, 0.0, 0.0), |n| normals[n]),
+                            texture_coordination: texture_coordination.map_or(vec2!(0.0, 0.0), |t| texture_coordinations[t]),
+                        });
+                        normal_sources.push((position, normal.is_some()));
+                        (vertices.len() - 1) as u32
+                    });
+                    corners.push(index);
+                }
+                if corners.len() < 3 {
+                    return Err(error(line, "a face needs at least 3 vertices"));
+                }
+                for n in 1..corners.len() - 1 {
+                    model.mesh.indices.extend_from_slice(&[corners[0], corners[n], corners[n + 1]]);
+                }
+            }
+            "o" | "g" | "usemtl" => {
+                let name = tokens.collect::<Vec<_>>().join(" ");
+                let mut next = ObjGroup {
+                    first_index: model.mesh.indices.len(),
+                    ..group.clone()
+                };
+                if keyword == "usemtl" {
+                    next.material = Some(name);
+                } else {
+                    next.name = name;
+                }
+                if next.name != group.name || next.material != group.material {
+                    close_group(&mut model, group);
+                    group = next;
+                }
+            }
+            "mtllib" => {
+                model.material_libraries.extend(tokens.map(String::from));
+            }
+            // Smoothing groups, lines, points and free-form geometry are ignored.
+            _ => {}
+        }
+    }
+    close_group(&mut model, group);
+
+    fill_missing_normals(&mut model.mesh, &normal_sources);
+    Ok(model)
+}
+
+// Gives the vertices that came without a normal the average of the faces
+// around them, weighted by corner angle. Only corners that also lack a normal
+// and use the same OBJ position are averaged, so authored normals stay as they
+// are and unrelated geometry at the same coordinates is not smoothed together.
+fn fill_missing_normals(mesh: &mut Mesh, normal_sources: &[(usize, bool)]) {
+    if normal_sources.iter().all(|&(_, has_normal)| has_normal) {
+        return;
+    }
+    let mut sums: HashMap<usize, Vector3> = HashMap::new();
+    for triangle in mesh.indices.chunks_exact(3) {
+        let p = |n: usize| mesh.vertices[triangle[n % 3] as usize].position;
+        let face = face_normal(p(0), p(1), p(2));
+        for n in 0..3 {
+            let (position, has_normal) = normal_sources[triangle[n] as usize];
+            if has_normal {
+                continue;
+            }
+            let sum = sums.entry(position).or_insert(vec3!(0.0, 0.0, 0.0));
+            *sum = *sum + face * corner_angle(p(n), p(n + 1), p(n + 2));
+        }
+    }
+
+    for (vertex, &(position, has_normal)) in mesh.vertices.iter_mut().zip(normal_sources) {
+        if !has_normal {
+            vertex.normal = safe_normalize(sums.get(&position).copied().unwrap_or(vec3!(0.0, 0.0, 0.0)));
+        }
+    }
+}
+
+fn close_group(model: &mut ObjModel, mut group: ObjGroup) {
+    group.index_count = model.mesh.indices.len() - group.first_index;
+    if group.index_count > 0 {
+        model.groups.push(group);
+    }
+}
+
+#[allow(dead_code)]
+pub fn parse_mtl(source: &str) -> Result<Vec<Material>, Error> {
+    let mut materials: Vec<Material> = Vec::new();
+
+    for (line, statement) in statements(source) {
+        let mut tokens = statement.split_whitespace();
+        let keyword = tokens.next().unwrap_or("");
+        if keyword == "newmtl" {
+            let name = tokens.collect::<Vec<_>>().join(" ");
+            if name.is_empty() {
+                return Err(error(line, "missing material name"));
+            }
+            materials.push(Material::new(&name));
+            continue;
+        }
+
+        let material = materials.last_mut()
+            .ok_or_else(|| error(line, format!("{} before newmtl", keyword)))?;
+        // Texture options such as `-s 1 1 1` come before the file name.
+        let texture = |tokens: std::str::SplitWhitespace| {
+            tokens.last().map(String::from).ok_or_else(|| error(line, "missing texture file name"))
+        };
+        match keyword {
+            "Ka" => material.ambient = parse_color(line, tokens)?,
+            "Kd" => material.diffuse = parse_color(line, tokens)?,
+            "Ks" => material.specular = parse_color(line, tokens)?,
+            "Ke" => material.emissive = parse_color(line, tokens)?,
+            "Ns" => material.shininess = parse_number(line, tokens.next(), "shininess")?,
+            "d" => material.opacity = parse_number(line, tokens.next(), "opacity")?,
+            "Tr" => material.opacity = 1.0 - parse_number::<f32>(line, tokens.next(), "transparency")?,
+            "illum" => material.illumination = parse_number(line, tokens.next(), "illumination model")?,
+            "map_Ka" => material.ambient_texture = Some(texture(tokens)?),
+            "map_Kd" => material.diffuse_texture = Some(texture(tokens)?),
+            "map_Ks" => material.specular_texture = Some(texture(tokens)?),
+            "map_d" => material.opacity_texture = Some(texture(tokens)?),
+            "map_Bump" | "map_bump" | "bump" | "norm" => material.normal_texture = Some(texture(tokens)?),
+            _ => {}
+        }
+    }
+
+    Ok(materials)
+}
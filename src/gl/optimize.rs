@@ -0,0 +1,269 @@
+use std::collections::HashMap;
+use std::mem::size_of;
+
+use super::{cast_slice, Pod};
+
+// Index buffer optimizations for indexed triangle lists. They work on any
+// vertex type and never change what is drawn, only the order it is drawn in.
+//
+// The usual pipeline is `weld`, then `optimize_vertex_cache`, then
+// `optimize_vertex_fetch`; `stripify` can follow for meshes drawn as
+// `DrawArrayMode::TriangleStrip`.
+
+// Merges vertices with identical bytes and drops vertices no triangle uses.
+// Returns the new vertices and indices. Positions that only differ in the sign
+// of a zero are kept apart, so normalize them first if that matters.
+pub fn weld<V: Pod>(vertices: &[V], indices: &[u32]) -> (Vec<V>, Vec<u32>) {
+    let mut welded = Vec::new();
+    let mut lookup: HashMap<&[u8], u32> = HashMap::new();
+    let mut remap: Vec<Option<u32>> = vec![None; vertices.len()];
+
+    let indices = indices.iter().map(|&i| {
+        *remap[i as usize].get_or_insert_with(|| {
+            let vertex = &vertices[i as usize];
+            *lookup.entry(bytes_of(vertex)).or_insert_with(|| {
+                welded.push(*vertex);
+                (welded.len() - 1) as u32
+            })
+        })
+    }).collect();
+
+    (welded, indices)
+}
+
+fn bytes_of<V: Pod>(vertex: &V) -> &[u8] {
+    if size_of::<V>() == 0 {
+        return &[];
+    }
+    cast_slice(std::slice::from_ref(vertex))
+}
+
+// Renumbers vertices in the order the indices first use them, so the GPU
+// reads the vertex buffer mostly front to back. Unused vertices are dropped.
+pub fn optimize_vertex_fetch<V: Pod>(vertices: &[V], indices: &[u32]) -> (Vec<V>, Vec<u32>) {
+    let mut reordered = Vec::with_capacity(vertices.len());
+    let mut remap: Vec<Option<u32>> = vec![None; vertices.len()];
+
+    let indices = indices.iter().map(|&i| {
+        *remap[i as usize].get_or_insert_with(|| {
+            reordered.push(vertices[i as usize]);
+            (reordered.len() - 1) as u32
+        })
+    }).collect();
+
+    (reordered, indices)
+}
+
+// Size of the LRU cache modelled by `optimize_vertex_cache`. Real caches are
+// smaller FIFOs, but the scoring is tuned for this size.
+const CACHE_SIZE: usize = 32;
+
+// Score of a vertex in Forsyth's algorithm: recently used vertices score high
+// (except the last triangle's, to avoid thin strips), and so do vertices with
+// few triangles left, so that no lonely triangles are left behind.
+fn vertex_score(cache_position: Option<usize>, remaining: usize) -> f32 {
+    if remaining == 0 {
+        return -1.0;
+    }
+    let cache_score = match cache_position {
+        None => 0.0,
+        Some(p) if p < 3 => 0.75,
+        Some(p) => (1.0 - (p - 3) as f32 / (CACHE_SIZE - 3) as f32).powf(1.5),
+    };
+    cache_score + 2.0 / (remaining as f32).sqrt()
+}
+
+// Reorders triangles for the post-transform vertex cache with Tom Forsyth's
+// "Linear-Speed Vertex Cache Optimisation". Each triangle keeps its winding.
+pub fn optimize_vertex_cache(indices: &[u32]) -> Vec<u32> {
+    let triangles: Vec<[usize; 3]> = indices.chunks_exact(3)
+        .map(|t| [t[0] as usize, t[1] as usize, t[2] as usize])
+        .collect();
+    let vertex_count = indices.iter().map(|&i| i as usize + 1).max().unwrap_or(0);
+
+    // Triangles not emitted yet, per vertex.
+    let mut adjacency: Vec<Vec<usize>> = vec![Vec::new(); vertex_count];
+    for (t, triangle) in triangles.iter().enumerate() {
+        for &v in triangle {
+            adjacency[v].push(t);
+        }
+    }
+
+    let mut cache_positions: Vec<Option<usize>> = vec![None; vertex_count];
+    let mut vertex_scores: Vec<f32> = adjacency.iter().map(|a| vertex_score(None, a.len())).collect();
+    let mut triangle_scores: Vec<f32> = triangles.iter()
+        .map(|t| t.iter().map(|&v| vertex_scores[v]).sum())
+        .collect();
+    let mut emitted = vec![false; triangles.len()];
+
+    let mut optimized = Vec::with_capacity(indices.len());
+    let mut cache: Vec<usize> = Vec::with_capacity(CACHE_SIZE + 3);
+    let mut best = best_triangle(0..triangles.len(), &triangle_scores, &emitted);
+    // Every triangle before it has been emitted. It only moves forward, so the
+    // restarts below take linear time in total.
+    let mut cursor = 0;
+
+    while let Some(t) = best {
+        emitted[t] = true;
+        optimized.extend(triangles[t].iter().map(|&v| v as u32));
+
+        // The triangle's vertices move to the front of the cache.
+        for &v in &triangles[t] {
+            adjacency[v].retain(|&other| other != t);
+        }
+        let previous = std::mem::take(&mut cache);
+        cache.extend_from_slice(&triangles[t]);
+        cache.extend(previous.into_iter().filter(|v| !triangles[t].contains(v)));
+        for &evicted in cache.iter().skip(CACHE_SIZE) {
+            cache_positions[evicted] = None;
+            vertex_scores[evicted] = vertex_score(None, adjacency[evicted].len());
+        }
+        cache.truncate(CACHE_SIZE);
+
+        // Only triangles around cached vertices change score.
+        for (p, &v) in cache.iter().enumerate() {
+            cache_positions[v] = Some(p);
+            vertex_scores[v] = vertex_score(Some(p), adjacency[v].len());
+        }
+        for &v in &cache {
+            for &other in &adjacency[v] {
+                triangle_scores[other] = triangles[other].iter().map(|&v| vertex_scores[v]).sum();
+            }
+        }
+
+        let candidates = cache.iter().flat_map(|&v| adjacency[v].iter().copied());
+        best = best_triangle(candidates, &triangle_scores, &emitted).or_else(|| {
+            // Nothing left around the cache; start over with the next triangle.
+            while cursor < triangles.len() && emitted[cursor] {
+                cursor += 1;
+            }
+            Some(cursor).filter(|&t| t < triangles.len())
+        });
+    }
+
+    optimized
+}
+
+fn best_triangle(candidates: impl Iterator<Item = usize>, scores: &[f32], emitted: &[bool]) -> Option<usize> {
+    candidates
+        .filter(|&t| !emitted[t])
+        .fold(None, |best: Option<usize>, t| match best {
+            Some(b) if scores[b] >= scores[t] => Some(b),
+            _ => Some(t),
+        })
+}
+
+// Fraction of vertex shader runs per triangle with a FIFO post-transform cache
+// of cache_size vertices. 3 means no reuse; well-ordered meshes get below 1.
+#[allow(dead_code)]
+pub fn average_cache_miss_ratio(indices: &[u32], cache_size: usize) -> f32 {
+    let triangle_count = indices.len() / 3;
+    if triangle_count == 0 {
+        return 0.0;
+    }
+    let mut cache: Vec<u32> = Vec::with_capacity(cache_size);
+    let mut misses = 0;
+    for &i in &indices[..triangle_count * 3] {
+        if !cache.contains(&i) {
+            misses += 1;
+            if cache.len() == cache_size {
+                cache.remove(0);
+            }
+            cache.push(i);
+        }
+    }
+    misses as f32 / triangle_count as f32
+}
+
+// Converts an indexed triangle list into indices of a single triangle strip
+// that draws the same triangles with the same winding. Separate strips are
+// joined with degenerate triangles, which the GPU skips. Degenerate triangles
+// in the input are dropped.
+pub fn stripify(indices: &[u32]) -> Vec<u32> {
+    let triangles: Vec<[u32; 3]> = indices.chunks_exact(3)
+        .map(|t| [t[0], t[1], t[2]])
+        .filter(|[a, b, c]| a != b && b != c && c != a)
+        .collect();
+
+    // Triangles by their directed edges, in winding order.
+    let mut edges: HashMap<(u32, u32), Vec<usize>> = HashMap::new();
+    for (t, &[a, b, c]) in triangles.iter().enumerate() {
+        for edge in [(a, b), (b, c), (c, a)] {
+            edges.entry(edge).or_default().push(t);
+        }
+    }
+
+    let mut used = vec![false; triangles.len()];
+    // An unused triangle entered across the directed edge (from, to), and its
+    // third vertex.
+    let neighbor = |used: &[bool], from: u32, to: u32| -> Option<(usize, u32)> {
+        edges.get(&(from, to))?.iter()
+            .find(|&&t| !used[t])
+            .map(|&t| {
+                let [a, b, c] = triangles[t];
+                let third = if a != from && a != to { a } else if b != from && b != to { b } else { c };
+                (t, third)
+            })
+    };
+
+    let mut strip: Vec<u32> = Vec::with_capacity(indices.len());
+    for start in 0..triangles.len() {
+        if used[start] {
+            continue;
+        }
+        used[start] = true;
+
+        // Begin with the rotation whose last edge continues the strip.
+        let [a, b, c] = triangles[start];
+        let rotations = [[a, b, c], [b, c, a], [c, a, b]];
+        let first = *rotations.iter()
+            .find(|[_, y, z]| neighbor(&used, *z, *y).is_some())
+            .unwrap_or(&rotations[0]);
+
+        let mut current = first.to_vec();
+        loop {
+            let n = current.len();
+            let (x, y) = (current[n - 2], current[n - 1]);
+            // Odd triangles of a strip are drawn as (y, x, z) to keep the winding.
+            let next = if (n - 2) % 2 == 0 { neighbor(&used, x, y) } else { neighbor(&used, y, x) };
+            match next {
+                Some((t, z)) => {
+                    used[t] = true;
+                    current.push(z);
+                }
+                None => break,
+            }
+        }
+
+        if let Some(&last) = strip.last() {
+            // The next strip must start on an even triangle to keep its winding.
+            if strip.len() % 2 == 1 {
+                strip.push(last);
+            }
+            strip.push(last);
+            strip.push(current[0]);
+        }
+        strip.extend_from_slice(&current);
+    }
+
+    strip
+}
+
+// Triangles drawn by a triangle strip, in list order and with the strip's
+// winding. Degenerate triangles are skipped.
+#[allow(dead_code)]
+pub fn unstripify(strip: &[u32]) -> Vec<u32> {
+    let mut indices = Vec::with_capacity(strip.len().saturating_sub(2) * 3);
+    for (n, window) in strip.windows(3).enumerate() {
+        let [a, b, c] = [window[0], window[1], window[2]];
+        if a == b || b == c || c == a {
+            continue;
+        }
+        if n % 2 == 0 {
+            indices.extend_from_slice(&[a, b, c]);
+        } else {
+            indices.extend_from_slice(&[b, a, c]);
+        }
+    }
+    indices
+}
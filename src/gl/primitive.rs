@@ -1,16 +1,18 @@
 
-use std::marker::PhantomData;
+use std::convert::TryInto;
 use std::mem::size_of;
-use std::mem::size_of_val;
 
 use crate::error::Error;
-use crate::math::Vector2;
-use crate::math::Vector3;
-use crate::math::Vector4;
+use crate::math::Aabb;
+use crate::vec3;
+use super::BufferUsage;
 use super::DrawArrayMode;
 use super::Drawable;
-
-
+use super::cast_slice;
+use super::ComponentType;
+use super::Index;
+use super::IndexType;
+use super::Vertex;
 use super::VertexAttribute;
 use super::GL;
 use web_sys::WebGl2RenderingContext;
@@ -19,92 +21,44 @@ use web_sys::WebGlVertexArrayObject;
 
 pub trait VertexAttributeArray {
     const DRAW_ARRAY_MODE: DrawArrayMode;
-    const POSITION: Option<VertexAttribute>;
-    const COLOR: Option<VertexAttribute>;
-    const TEXTURE_COORDINATION: Option<VertexAttribute>;
-    
-    // Pointer to the first element of vertex attribute array.
-    fn as_slice(&self) -> &[f32];
+    const ATTRIBUTES: &'static [VertexAttribute];
+
+    // Raw bytes of the vertex attribute array.
+    fn as_bytes(&self) -> &[u8];
     // Number of vertices in the vertex attribute array.
     fn vertex_count(&self) -> i32;
 }
 
-pub struct UVMappedSliceTriangleStrip<'a>(pub &'a [(Vector3, Vector2)]);
-
-impl<'a> VertexAttributeArray for UVMappedSliceTriangleStrip<'a> {
-    const DRAW_ARRAY_MODE: DrawArrayMode = DrawArrayMode::TriangleStrip;
-    const POSITION: Option<VertexAttribute> = Some(VertexAttribute { offset: 0, size: 3, stride: 20 });
-    const COLOR: Option<VertexAttribute> = None;
-    const TEXTURE_COORDINATION: Option<VertexAttribute> = Some(VertexAttribute { offset: 12, size: 2, stride: 20 });
-
-    fn as_slice(&self) -> &[f32] {
-        unsafe {
-            // HACK:
-            // Yes, this is really unsafe and causes undefined behavior.
-            // Rust's tuple virtually has `repr(rust)` so compiler CAN reorder the member.
-            // as far as I know, but, it seems that the order of the tuple members are kept.
-            //
-            // If we want to make this "safe", we should define Vector3 as repr(C) struct, instead of tuple. 
-            // It'll be obvious hurt writing pattern maching against Vector3. Need macro...
-            std::slice::from_raw_parts(&self.0[0].0.x as *const f32, size_of_val(self.0) / size_of::<f32>())
-        }
-    }
-
-    fn vertex_count(&self) -> i32 {
-        self.0.len() as i32
-    }
+// Wraps a slice of vertices to be drawn with the given mode.
+macro_rules! slice_vertex_attribute_array {
+    ($($name:ident => $mode:ident;)*) => {
+        $(
+            pub struct $name<'a, V: Vertex>(pub &'a [V]);
+
+            impl<'a, V: Vertex> VertexAttributeArray for $name<'a, V> {
+                const DRAW_ARRAY_MODE: DrawArrayMode = DrawArrayMode::$mode;
+                const ATTRIBUTES: &'static [VertexAttribute] = V::ATTRIBUTES;
+
+                fn as_bytes(&self) -> &[u8] {
+                    cast_slice(self.0)
+                }
+
+                fn vertex_count(&self) -> i32 {
+                    self.0.len() as i32
+                }
+            }
+        )*
+    };
 }
 
-pub struct UVMappedSliceTriangleList<'a>(pub &'a [(Vector3, Vector2)]);
-
-impl<'a> VertexAttributeArray for UVMappedSliceTriangleList<'a> {
-    const DRAW_ARRAY_MODE: DrawArrayMode = DrawArrayMode::TriangleList;
-    const POSITION: Option<VertexAttribute> = Some(VertexAttribute { offset: 0, size: 3, stride: 20 });
-    const COLOR: Option<VertexAttribute> = None;
-    const TEXTURE_COORDINATION: Option<VertexAttribute> = Some(VertexAttribute { offset: 12, size: 2, stride: 20 });
-
-    fn as_slice(&self) -> &[f32] {
-        unsafe {
-            // HACK:
-            // Yes, this is really unsafe and causes undefined behavior.
-            // Rust's tuple virtually has `repr(rust)` so compiler CAN reorder the member.
-            // as far as I know, but, it seems that the order of the tuple members are kept.
-            //
-            // If we want to make this "safe", we should define Vector3 as repr(C) struct, instead of tuple. 
-            // It'll be obvious hurt writing pattern maching against Vector3. Need macro...
-            std::slice::from_raw_parts(&self.0[0].0.x as *const f32, self.vertex_count() as usize * 5)
-        }
-    }
-
-    fn vertex_count(&self) -> i32 {
-        self.0.len() as i32
-    }
-}
-
-pub struct ColoredSliceTriangleStrip<'a>(pub &'a [(Vector3, Vector4)]);
-
-impl<'a> VertexAttributeArray for ColoredSliceTriangleStrip<'a> {
-    const DRAW_ARRAY_MODE: DrawArrayMode = DrawArrayMode::TriangleStrip;
-    const POSITION: Option<VertexAttribute> = Some(VertexAttribute { offset: 0, size: 3, stride: 28 });
-    const COLOR: Option<VertexAttribute> = Some(VertexAttribute { offset: size_of::<Vector3>(), size:  4, stride: 28 });
-    const TEXTURE_COORDINATION: Option<VertexAttribute> = None;
-
-    fn as_slice(&self) -> &[f32] {
-        unsafe {
-            // HACK:
-            // Yes, this is really unsafe and causes undefined behavior.
-            // Rust's tuple virtually has `repr(rust)` so compiler CAN reorder the member.
-            // as far as I know, but, it seems that the order of the tuple members are kept.
-            //
-            // If we want to make this "safe", we should define Vector3 as repr(C) struct, instead of tuple. 
-            // It'll be obvious hurt writing pattern maching against Vector3. Need macro...
-            std::slice::from_raw_parts(&self.0[0].0.x as *const f32, size_of_val(self.0) / size_of::<f32>())
-        }
-    }
-
-    fn vertex_count(&self) -> i32 {
-        self.0.len() as i32
-    }
+slice_vertex_attribute_array! {
+    SlicePoints => Points;
+    SliceLines => Lines;
+    SliceLineStrip => LineStrip;
+    SliceLineLoop => LineLoop;
+    SliceTriangleStrip => TriangleStrip;
+    SliceTriangleList => TriangleList;
+    SliceTriangleFan => TriangleFan;
 }
 
 pub struct TriangleStrip {
@@ -113,12 +67,18 @@ pub struct TriangleStrip {
 
 impl VertexAttributeArray for TriangleStrip {
     const DRAW_ARRAY_MODE: DrawArrayMode = DrawArrayMode::TriangleStrip;
-    const POSITION: Option<VertexAttribute> = Some(VertexAttribute { offset: 0, size: 3, stride: 0 });
-    const COLOR: Option<VertexAttribute> = None;
-    const TEXTURE_COORDINATION: Option<VertexAttribute> = None;
-
-    fn as_slice(&self) -> &[f32] {
-        self.vertices.as_slice()
+    const ATTRIBUTES: &'static [VertexAttribute] = &[VertexAttribute {
+        name: "position",
+        offset: 0,
+        size: 3,
+        stride: 0,
+        component_type: ComponentType::Float,
+        normalized: false,
+        integer: false,
+    }];
+
+    fn as_bytes(&self) -> &[u8] {
+        cast_slice(self.vertices.as_slice())
     }
 
     fn vertex_count(&self) -> i32 {
@@ -131,10 +91,59 @@ pub struct Primitive {
     vao: WebGlVertexArrayObject,
     vertex_count: i32,
     draw_array_mode: DrawArrayMode,
-    position: Option<VertexAttribute>,
-    color: Option<VertexAttribute>,
-    texture_coordination: Option<VertexAttribute>,
-    _vertex_buffer: WebGlBuffer,
+    bounding_box: Option<Aabb>,
+    index_type: Option<IndexType>,
+    index_count: i32,
+    instances: Option<Instances>,
+    // Stream 0 is the buffer given to `new`.
+    streams: Vec<VertexStream>,
+    submeshes: Vec<Submesh>,
+    _index_buffer: Option<WebGlBuffer>,
+}
+
+// A named range of a primitive drawn with its own material, so that models
+// with several materials share one vertex array object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Submesh {
+    pub name: String,
+    // First index, or first vertex when the primitive has no indices.
+    pub first: i32,
+    pub count: i32,
+    // Index into the caller's list of materials.
+    pub material: usize,
+}
+
+struct VertexStream {
+    buffer: WebGlBuffer,
+    attributes: &'static [VertexAttribute],
+}
+
+struct Instances {
+    buffer: WebGlBuffer,
+    attributes: &'static [VertexAttribute],
+    count: i32,
+}
+
+// Bounding box of the float attribute named "position".
+fn position_bounds(data: &[u8], attributes: &[VertexAttribute], vertex_count: i32) -> Option<Aabb> {
+    let position = attributes.iter()
+        .find(|attr| attr.name == "position" && attr.component_type == ComponentType::Float)?;
+    let stride = if position.stride == 0 {
+        position.size * size_of::<f32>()
+    } else {
+        position.stride
+    };
+
+    Aabb::from_points((0..vertex_count as usize).map(|i| {
+        let v = &data[position.offset + i * stride..];
+        let component = |n: usize| if n < position.size {
+            let bytes = &v[n * size_of::<f32>()..][..size_of::<f32>()];
+            f32::from_ne_bytes(bytes.try_into().unwrap())
+        } else {
+            0.0
+        };
+        vec3!(component(0), component(1), component(2))
+    }))
 }
 
 fn transfer<T: VertexAttributeArray>(gl: &GL, data: T, vao: &WebGlVertexArrayObject, buffer: &WebGlBuffer) {
@@ -143,7 +152,7 @@ fn transfer<T: VertexAttributeArray>(gl: &GL, data: T, vao: &WebGlVertexArrayObj
     ctx.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, Some(buffer));
 
     unsafe {
-        let view = js_sys::Float32Array::view(data.as_slice());
+        let view = js_sys::Uint8Array::view(data.as_bytes());
         ctx.buffer_data_with_array_buffer_view(
             WebGl2RenderingContext::ARRAY_BUFFER,
             &view,
@@ -154,6 +163,51 @@ fn transfer<T: VertexAttributeArray>(gl: &GL, data: T, vao: &WebGlVertexArrayObj
     ctx.bind_vertex_array(None);
 }
 
+// Every index must refer to one of the vertex_count vertices.
+pub fn check_indices<I: Index>(indices: &[I], vertex_count: i32) -> Result<(), Error> {
+    match indices.iter().position(|i| i.to_usize() >= vertex_count as usize) {
+        Some(n) => Err(format!(
+            "index {} at {} is out of range for {} vertices",
+            indices[n].to_usize(), n, vertex_count).into()),
+        None => Ok(()),
+    }
+}
+
+// The range [first, first + count) must lie within total elements.
+pub fn check_draw_range(first: i32, count: i32, total: i32) -> Result<(), Error> {
+    if first < 0 || count < 0 || first as i64 + count as i64 > total as i64 {
+        return Err(format!("range {}..{} is out of bounds for {} elements", first, first as i64 + count as i64, total).into());
+    }
+    Ok(())
+}
+
+// Attribute names must be unique across the streams of a primitive.
+pub fn check_stream_attributes(streams: &[&[VertexAttribute]], attributes: &[VertexAttribute]) -> Result<(), Error> {
+    let existing = streams.iter().flat_map(|stream| stream.iter());
+    match existing.map(|a| a.name).find(|name| attributes.iter().any(|b| b.name == *name)) {
+        Some(name) => Err(format!("attribute {} is already in another stream", name).into()),
+        None => Ok(()),
+    }
+}
+
+// The element array binding is part of the vertex array object state.
+fn transfer_indices<I: Index>(gl: &GL, indices: &[I], vao: &WebGlVertexArrayObject, buffer: &WebGlBuffer) {
+    let ctx = gl.context();
+    ctx.bind_vertex_array(Some(vao));
+    ctx.bind_buffer(WebGl2RenderingContext::ELEMENT_ARRAY_BUFFER, Some(buffer));
+
+    unsafe {
+        let view = js_sys::Uint8Array::view(cast_slice(indices));
+        ctx.buffer_data_with_array_buffer_view(
+            WebGl2RenderingContext::ELEMENT_ARRAY_BUFFER,
+            &view,
+            WebGl2RenderingContext::STATIC_DRAW,
+        );
+    }
+
+    ctx.bind_vertex_array(None);
+}
+
 impl Primitive {
     pub fn new<T: VertexAttributeArray>(
         gl: &GL,
@@ -167,6 +221,7 @@ impl Primitive {
             .create_buffer()
             .ok_or("createBuffer failed.")?;
         let vertex_count = data.vertex_count();
+        let bounding_box = position_bounds(data.as_bytes(), T::ATTRIBUTES, vertex_count);
 
         transfer(gl, data, &vao, &buffer);
 
@@ -175,91 +230,202 @@ impl Primitive {
             vao,
             vertex_count,
             draw_array_mode: T::DRAW_ARRAY_MODE,
-            position: T::POSITION,
-            color: T::COLOR,
-            texture_coordination: T::TEXTURE_COORDINATION,
-            _vertex_buffer: buffer,
+            bounding_box,
+            index_type: None,
+            index_count: 0,
+            instances: None,
+            streams: vec![VertexStream { buffer, attributes: T::ATTRIBUTES }],
+            submeshes: Vec::new(),
+            _index_buffer: None,
         })
     }
-}
 
-impl Drawable for Primitive {
-    fn draw_array_mode(&self) -> DrawArrayMode {
-        self.draw_array_mode
+    // Vertices are drawn in the order of indices, so they can be shared between primitives.
+    pub fn new_indexed<T: VertexAttributeArray, I: Index>(
+        gl: &GL,
+        data: T,
+        indices: &[I],
+    ) -> Result<Primitive, Error> {
+        check_indices(indices, data.vertex_count())?;
+
+        let mut primitive = Self::new(gl, data)?;
+        let buffer = gl.context()
+            .create_buffer()
+            .ok_or("createBuffer failed.")?;
+
+        transfer_indices(gl, indices, &primitive.vao, &buffer);
+
+        primitive.index_type = Some(I::INDEX_TYPE);
+        primitive.index_count = indices.len() as i32;
+        primitive._index_buffer = Some(buffer);
+        Ok(primitive)
     }
-    
-    fn position(&self) -> Option<VertexAttribute> {
-        self.position
+
+    // Draws the primitive once per element of `instances`, with the fields of
+    // `V` as per-instance attributes. Replaces the previous instance data.
+    //
+    // Call `Shader::enable_vertex_attribute` again after the first call and
+    // whenever the instance type changes, so the instance attributes are bound.
+    // The bounding box does not take instances into account.
+    #[allow(dead_code)]
+    pub fn set_instances<V: Vertex>(&mut self, instances: &[V]) -> Result<(), Error> {
+        let ctx = self.gl.context();
+        let buffer = match self.instances.take() {
+            Some(Instances { buffer, .. }) => buffer,
+            None => ctx.create_buffer().ok_or("createBuffer failed.")?,
+        };
+
+        ctx.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, Some(&buffer));
+        ctx.buffer_data_with_u8_array(
+            WebGl2RenderingContext::ARRAY_BUFFER,
+            cast_slice(instances),
+            WebGl2RenderingContext::DYNAMIC_DRAW,
+        );
+        ctx.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, None);
+
+        self.instances = Some(Instances {
+            buffer,
+            attributes: V::ATTRIBUTES,
+            count: instances.len() as i32,
+        });
+        Ok(())
     }
-    
-    fn color(&self) -> Option<VertexAttribute> {
-        self.color
+
+    // Adds a vertex buffer with more attributes of the same vertices, so that
+    // e.g. colors can be updated without uploading positions again. `vertices`
+    // has one element per vertex. Returns the stream for `update_stream`.
+    //
+    // Call `Shader::enable_vertex_attribute` again after adding a stream.
+    #[allow(dead_code)]
+    pub fn add_stream<V: Vertex>(&mut self, vertices: &[V], usage: BufferUsage) -> Result<usize, Error> {
+        self.check_stream_length(vertices.len())?;
+        let existing: Vec<_> = self.streams.iter().map(|stream| stream.attributes).collect();
+        check_stream_attributes(&existing, V::ATTRIBUTES)?;
+
+        let ctx = self.gl.context();
+        let buffer = ctx.create_buffer().ok_or("createBuffer failed.")?;
+        ctx.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, Some(&buffer));
+        ctx.buffer_data_with_u8_array(WebGl2RenderingContext::ARRAY_BUFFER, cast_slice(vertices), usage.into());
+        ctx.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, None);
+
+        self.update_bounding_box(vertices);
+        self.streams.push(VertexStream { buffer, attributes: V::ATTRIBUTES });
+        Ok(self.streams.len() - 1)
     }
 
-    fn texture_coordination(&self) -> Option<VertexAttribute> {
-        self.texture_coordination
+    // Replaces the contents of a stream. The vertex type must be the one the
+    // stream was created with, and the vertex count must not change.
+    #[allow(dead_code)]
+    pub fn update_stream<V: Vertex>(&mut self, stream: usize, vertices: &[V]) -> Result<(), Error> {
+        self.check_stream_length(vertices.len())?;
+        let target = self.streams.get(stream)
+            .ok_or_else(|| Error::from(format!("stream {} does not exist", stream)))?;
+        if target.attributes != V::ATTRIBUTES {
+            return Err(format!("stream {} has different attributes", stream).into());
+        }
+
+        let ctx = self.gl.context();
+        ctx.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, Some(&target.buffer));
+        ctx.buffer_sub_data_with_i32_and_u8_array(WebGl2RenderingContext::ARRAY_BUFFER, 0, cast_slice(vertices));
+        ctx.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, None);
+
+        self.update_bounding_box(vertices);
+        Ok(())
     }
 
-    fn vertex_count(&self) -> i32 {
-        self.vertex_count
+    fn check_stream_length(&self, length: usize) -> Result<(), Error> {
+        if length != self.vertex_count as usize {
+            return Err(format!("stream has {} vertices, expected {}", length, self.vertex_count).into());
+        }
+        Ok(())
     }
 
-    fn vertex_array_object(&self) -> &WebGlVertexArrayObject {
-        &self.vao
+    // Only streams with positions affect the bounding box.
+    fn update_bounding_box<V: Vertex>(&mut self, vertices: &[V]) {
+        if let Some(bounding_box) = position_bounds(cast_slice(vertices), V::ATTRIBUTES, self.vertex_count) {
+            self.bounding_box = Some(bounding_box);
+        }
     }
-}
 
-impl Drop for Primitive {
-    fn drop(&mut self) {
-        let ctx = self.gl.context();
-        ctx.delete_vertex_array(Some(&self.vao));
-        ctx.delete_buffer(Some(&self._vertex_buffer));
+    // Adds a range to draw with `Shader::draw_submesh`. first and count are in
+    // indices, or in vertices when the primitive has no indices. Returns the
+    // position of the submesh in `submeshes`.
+    #[allow(dead_code)]
+    pub fn add_submesh(&mut self, name: &str, first: i32, count: i32, material: usize) -> Result<usize, Error> {
+        let total = if self.index_type.is_some() { self.index_count } else { self.vertex_count };
+        check_draw_range(first, count, total)?;
+        if self.submesh(name).is_some() {
+            return Err(format!("submesh {} already exists", name).into());
+        }
+        self.submeshes.push(Submesh { name: name.to_string(), first, count, material });
+        Ok(self.submeshes.len() - 1)
     }
-}
 
-pub struct EphemeralPrimitive<'a, T: VertexAttributeArray> {
-    vao: &'a WebGlVertexArrayObject,
-    _buffer: &'a WebGlBuffer,
-    vertex_count: i32,
-    _phantom_data: PhantomData<T>
-}
+    #[allow(dead_code)]
+    pub fn submeshes(&self) -> &[Submesh] {
+        &self.submeshes
+    }
 
-impl<'a, T: VertexAttributeArray> EphemeralPrimitive<'a, T> {
-    pub fn transfer(gl: &GL, data: T, vao: &'a WebGlVertexArrayObject, buffer: &'a WebGlBuffer) -> Self {
-        let vertex_count = data.vertex_count();
-        super::primitive::transfer(gl, data, vao, buffer);
+    #[allow(dead_code)]
+    pub fn submesh(&self, name: &str) -> Option<&Submesh> {
+        self.submeshes.iter().find(|submesh| submesh.name == name)
+    }
 
-        EphemeralPrimitive {
-            vao,
-            _buffer: buffer,
-            vertex_count,
-            _phantom_data: PhantomData
-        }
+    // Bounding box of the vertex positions in model space.
+    // None when there is no position attribute or no vertex.
+    pub fn bounding_box(&self) -> Option<Aabb> {
+        self.bounding_box
     }
 }
 
-impl<'a, T: VertexAttributeArray> Drawable for EphemeralPrimitive<'a, T> {
+impl Drawable for Primitive {
     fn draw_array_mode(&self) -> DrawArrayMode {
-        T::DRAW_ARRAY_MODE
+        self.draw_array_mode
+    }
+    
+    fn vertex_streams(&self) -> Vec<(&WebGlBuffer, &'static [VertexAttribute])> {
+        self.streams.iter().map(|stream| (&stream.buffer, stream.attributes)).collect()
     }
 
-    fn position(&self) -> Option<VertexAttribute> {
-        T::POSITION
+    fn instance_attributes(&self) -> Option<(&WebGlBuffer, &'static [VertexAttribute])> {
+        self.instances.as_ref().map(|i| (&i.buffer, i.attributes))
     }
 
-    fn color(&self) -> Option<VertexAttribute> {
-        T::COLOR
+    fn instance_count(&self) -> Option<i32> {
+        self.instances.as_ref().map(|i| i.count)
     }
 
-    fn texture_coordination(&self) -> Option<VertexAttribute> {
-        T::TEXTURE_COORDINATION
+    fn first_vertex(&self) -> i32 {
+        0
     }
 
     fn vertex_count(&self) -> i32 {
         self.vertex_count
     }
 
+    fn index_type(&self) -> Option<IndexType> {
+        self.index_type
+    }
+
+    fn index_count(&self) -> i32 {
+        self.index_count
+    }
+
     fn vertex_array_object(&self) -> &WebGlVertexArrayObject {
-        self.vao
+        &self.vao
+    }
+}
+
+impl Drop for Primitive {
+    fn drop(&mut self) {
+        let ctx = self.gl.context();
+        ctx.delete_vertex_array(Some(&self.vao));
+        for stream in &self.streams {
+            ctx.delete_buffer(Some(&stream.buffer));
+        }
+        if let Some(instances) = &self.instances {
+            ctx.delete_buffer(Some(&instances.buffer));
+        }
+        ctx.delete_buffer(self._index_buffer.as_ref());
     }
 }
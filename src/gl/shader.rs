@@ -1,14 +1,17 @@
 use web_sys::WebGl2RenderingContext;
 
+use web_sys::WebGlBuffer;
 use web_sys::WebGlProgram;
 use web_sys::WebGlShader;
 use web_sys::WebGlVertexArrayObject;
 
 use crate::error::Error;
+use crate::math::Matrix3;
 use crate::math::Matrix4;
 use crate::math::Vector4;
 use crate::vec4;
 use super::GL;
+use super::{check_draw_range, Primitive, Submesh};
 
 pub struct Shader {
     gl: GL,
@@ -17,36 +20,154 @@ pub struct Shader {
     fragment_shader: WebGlShader,
 }
 
-#[derive(Clone, Copy)]
+// Layout of one shader input inside a vertex buffer.
+// Offset and stride are in bytes; zero stride means tightly packed.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct VertexAttribute {
+    // Name of the `in` variable in the vertex shader.
+    pub name: &'static str,
     pub offset: usize,
+    // Number of components. Sizes above 4 (e.g. a mat4) take consecutive
+    // locations of 4 components each; see `slots`.
     pub size: usize,
     pub stride: usize,
+    pub component_type: ComponentType,
+    // Integer components are mapped to [0, 1] (unsigned) or [-1, 1] (signed).
+    pub normalized: bool,
+    // Integer components are passed to ivec/uvec inputs as is, via vertexAttribIPointer.
+    // Otherwise they are converted to float.
+    pub integer: bool,
 }
 
-#[derive(Clone, Copy)]
+impl VertexAttribute {
+    // A shader attribute location holds at most 4 components. Larger attributes
+    // (e.g. a mat4) take consecutive locations of 4 components each.
+    pub fn slots(&self) -> impl Iterator<Item = VertexAttribute> + '_ {
+        let slot_bytes = 4 * self.component_type.size();
+        (0..self.size).step_by(4).map(move |first| VertexAttribute {
+            offset: self.offset + first / 4 * slot_bytes,
+            size: (self.size - first).min(4),
+            ..*self
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComponentType {
+    Byte,
+    UnsignedByte,
+    Short,
+    UnsignedShort,
+    Int,
+    UnsignedInt,
+    Float,
+    HalfFloat,
+}
+
+impl ComponentType {
+    pub const fn is_integer(self) -> bool {
+        !matches!(self, ComponentType::Float | ComponentType::HalfFloat)
+    }
+
+    // Size of one component in bytes.
+    pub fn size(self) -> usize {
+        match self {
+            ComponentType::Byte | ComponentType::UnsignedByte => 1,
+            ComponentType::Short | ComponentType::UnsignedShort | ComponentType::HalfFloat => 2,
+            ComponentType::Int | ComponentType::UnsignedInt | ComponentType::Float => 4,
+        }
+    }
+}
+
+impl From<ComponentType> for u32 {
+    fn from(val: ComponentType) -> Self {
+        match val {
+            ComponentType::Byte => WebGl2RenderingContext::BYTE,
+            ComponentType::UnsignedByte => WebGl2RenderingContext::UNSIGNED_BYTE,
+            ComponentType::Short => WebGl2RenderingContext::SHORT,
+            ComponentType::UnsignedShort => WebGl2RenderingContext::UNSIGNED_SHORT,
+            ComponentType::Int => WebGl2RenderingContext::INT,
+            ComponentType::UnsignedInt => WebGl2RenderingContext::UNSIGNED_INT,
+            ComponentType::Float => WebGl2RenderingContext::FLOAT,
+            ComponentType::HalfFloat => WebGl2RenderingContext::HALF_FLOAT,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DrawArrayMode {
+    Points,
+    Lines,
+    LineStrip,
+    // Like LineStrip, but also connects the last vertex to the first.
+    LineLoop,
     TriangleStrip,
-    TriangleList
+    TriangleList,
+    TriangleFan,
 }
 
 impl From<DrawArrayMode> for u32 {
     fn from(val: DrawArrayMode) -> Self {
         match val {
+            DrawArrayMode::Points => WebGl2RenderingContext::POINTS,
+            DrawArrayMode::Lines => WebGl2RenderingContext::LINES,
+            DrawArrayMode::LineStrip => WebGl2RenderingContext::LINE_STRIP,
+            DrawArrayMode::LineLoop => WebGl2RenderingContext::LINE_LOOP,
             DrawArrayMode::TriangleStrip => WebGl2RenderingContext::TRIANGLE_STRIP,
-            DrawArrayMode::TriangleList => WebGl2RenderingContext::TRIANGLES
+            DrawArrayMode::TriangleList => WebGl2RenderingContext::TRIANGLES,
+            DrawArrayMode::TriangleFan => WebGl2RenderingContext::TRIANGLE_FAN,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexType {
+    U8,
+    U16,
+    U32,
+}
+
+impl IndexType {
+    // Size of one index in bytes.
+    pub fn size(self) -> usize {
+        match self {
+            IndexType::U8 => 1,
+            IndexType::U16 => 2,
+            IndexType::U32 => 4,
+        }
+    }
+}
+
+impl From<IndexType> for u32 {
+    fn from(val: IndexType) -> Self {
+        match val {
+            IndexType::U8 => WebGl2RenderingContext::UNSIGNED_BYTE,
+            IndexType::U16 => WebGl2RenderingContext::UNSIGNED_SHORT,
+            IndexType::U32 => WebGl2RenderingContext::UNSIGNED_INT,
         }
     }
 }
 
 pub trait Drawable {
-    fn position(&self) -> Option<VertexAttribute>;
-    fn color(&self) -> Option<VertexAttribute>;
-    fn texture_coordination(&self) -> Option<VertexAttribute>;
+    // Buffers holding the per-vertex attributes, each with the attributes read
+    // from it. Every buffer has one element per vertex.
+    fn vertex_streams(&self) -> Vec<(&WebGlBuffer, &'static [VertexAttribute])>;
+    // Per-instance attributes and the buffer they are read from.
+    // They advance once per instance instead of once per vertex.
+    fn instance_attributes(&self) -> Option<(&WebGlBuffer, &'static [VertexAttribute])>;
+    // Number of instances to draw. None draws once without instancing.
+    fn instance_count(&self) -> Option<i32>;
 
     fn draw_array_mode(&self) -> DrawArrayMode;
+    // First vertex drawn by drawArrays.
+    fn first_vertex(&self) -> i32;
     // Number of vertices in the vertex attribute array.
     fn vertex_count(&self) -> i32;
+    // Type of the element array bound to the vertex array object.
+    // None draws the vertices in order.
+    fn index_type(&self) -> Option<IndexType>;
+    // Number of indices in the element array.
+    fn index_count(&self) -> i32;
     fn vertex_array_object(&self) -> &WebGlVertexArrayObject;
 }
 
@@ -93,25 +214,58 @@ impl Shader {
 
         ctx.bind_vertex_array(Some(vao));
 
-        if let Some(position) = obj.position() {
-            Self::enable_vertex_attribute_array(ctx, &self.program, "position", position)
+        // Attributes the shader does not declare are skipped.
+        for (buffer, attributes) in obj.vertex_streams() {
+            ctx.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, Some(buffer));
+            for attr in attributes {
+                Self::enable_vertex_attribute_array(ctx, &self.program, attr, 0)
+            }
         }
-        if let Some(color) = obj.color() {
-            Self::enable_vertex_attribute_array(ctx, &self.program, "color", color)
-        }
-        if let Some(texture_coordination) = obj.texture_coordination() {
-            Self::enable_vertex_attribute_array(ctx, &self.program, "textureCoord", texture_coordination)
+        if let Some((buffer, attributes)) = obj.instance_attributes() {
+            ctx.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, Some(buffer));
+            for attr in attributes {
+                Self::enable_vertex_attribute_array(ctx, &self.program, attr, 1)
+            }
         }
 
         ctx.bind_vertex_array(None);
+        ctx.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, None);
     }
 
     pub fn draw<T: Drawable>(&self, obj: &T) {
+        match obj.index_type() {
+            Some(_) => self.draw_range(obj, 0, obj.index_count()),
+            None => self.draw_range(obj, obj.first_vertex(), obj.vertex_count()),
+        }
+    }
+
+    // Draws one submesh of obj. Fails when the submesh's range does not fit
+    // in obj, e.g. because it came from another primitive.
+    #[allow(dead_code)]
+    pub fn draw_submesh(&self, obj: &Primitive, submesh: &Submesh) -> Result<(), Error> {
+        let total = if obj.index_type().is_some() { obj.index_count() } else { obj.vertex_count() };
+        check_draw_range(submesh.first, submesh.count, total)?;
+        self.draw_range(obj, submesh.first, submesh.count);
+        Ok(())
+    }
+
+    // Draws count elements starting at first: indices when the object has an
+    // element array, vertices otherwise. The range must already be checked.
+    fn draw_range<T: Drawable>(&self, obj: &T, first: i32, count: i32) {
         let ctx = self.ctx();
         let vao = obj.vertex_array_object();
         ctx.bind_vertex_array(Some(vao));
-        ctx 
-            .draw_arrays(obj.draw_array_mode().into(), 0, obj.vertex_count());
+        let mode = obj.draw_array_mode().into();
+        match (obj.index_type(), obj.instance_count()) {
+            (Some(index_type), None) => ctx
+                .draw_elements_with_i32(mode, count, index_type.into(), first * index_type.size() as i32),
+            (Some(index_type), Some(instances)) => ctx
+                .draw_elements_instanced_with_i32(mode, count, index_type.into(), first * index_type.size() as i32, instances),
+            (None, None) => ctx
+                .draw_arrays(mode, first, count),
+            (None, Some(instances)) => ctx
+                .draw_arrays_instanced(mode, first, count, instances),
+        }
         ctx.bind_vertex_array(None);
     }
 
@@ -124,17 +278,32 @@ impl Shader {
         Some(idx as u32)
     }
 
-    fn enable_vertex_attribute_array(ctx: &WebGl2RenderingContext, program: &WebGlProgram, name: &str, attr: VertexAttribute) {
-        if let Some(idx) = Self::get_attrib_location(ctx, program, name) {
-            ctx.vertex_attrib_pointer_with_i32(
-                idx,
-                attr.size as i32,
-                WebGl2RenderingContext::FLOAT,
-                false,
-                attr.stride as i32,
-                attr.offset as i32
-            );
-            ctx.enable_vertex_attrib_array(idx);
+    // Divisor 0 advances the attribute per vertex, n per n instances.
+    fn enable_vertex_attribute_array(ctx: &WebGl2RenderingContext, program: &WebGlProgram, attr: &VertexAttribute, divisor: u32) {
+        if let Some(idx) = Self::get_attrib_location(ctx, program, attr.name) {
+            for (n, slot) in attr.slots().enumerate() {
+                let idx = idx + n as u32;
+                if slot.integer {
+                    ctx.vertex_attrib_i_pointer_with_i32(
+                        idx,
+                        slot.size as i32,
+                        slot.component_type.into(),
+                        slot.stride as i32,
+                        slot.offset as i32
+                    );
+                } else {
+                    ctx.vertex_attrib_pointer_with_i32(
+                        idx,
+                        slot.size as i32,
+                        slot.component_type.into(),
+                        slot.normalized,
+                        slot.stride as i32,
+                        slot.offset as i32
+                    );
+                }
+                ctx.vertex_attrib_divisor(idx, divisor);
+                ctx.enable_vertex_attrib_array(idx);
+            }
         }
     }
 
@@ -144,6 +313,13 @@ impl Shader {
         ctx.uniform_matrix4fv_with_f32_array(idx.as_ref(), true, uniform.as_ref());
     }
 
+    #[allow(dead_code)]
+    pub fn set_uniform_normal_matrix(&self, uniform: &Matrix3) {
+        let ctx = self.ctx();
+        let idx = ctx.get_uniform_location(&self.program, "normalMatrix");
+        ctx.uniform_matrix3fv_with_f32_array(idx.as_ref(), true, uniform.as_ref());
+    }
+
     #[allow(dead_code)]
     pub fn set_uniform_texture(&self, texture_unit: i32) {
         let ctx = self.ctx();
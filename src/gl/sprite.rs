@@ -1,24 +1,22 @@
 
 
 use log::trace;
-use web_sys::{WebGl2RenderingContext, WebGlBuffer, WebGlTexture, WebGlVertexArrayObject};
+use web_sys::{WebGl2RenderingContext, WebGlTexture};
 
-use crate::{error::Error, mat4, math::{Matrix4, Rectangle, Size, Vector2, Vector3, Vector4}, vec2, vec4};
+use crate::{error::Error, mat4, math::{Matrix4, RectF, Size, Vector2, Vector4}, vec2};
 
-use super::{EphemeralPrimitive, Shader, UVMappedSliceTriangleList, GL};
+use super::{BufferUsage, DrawArrayMode, DynamicPrimitive, Shader, UVMappedVertex, GL};
 
 pub struct Sprite {
     gl: GL,
     shader: Shader,
-    vao: WebGlVertexArrayObject,
-    vbuf: WebGlBuffer,
+    vertices: DynamicPrimitive<UVMappedVertex>,
     screen_size: Size,
 }
 
 impl Sprite {
     pub fn new(gl: &GL, screen_size: Size) -> Result<Sprite, Error> {
         trace!("Initializing Sprite...");
-        let ctx = gl.context();
         let vert_shader_source = r##"#version 300 es
             in vec4 position;
             in vec2 textureCoord;
@@ -45,11 +43,11 @@ impl Sprite {
             frag_shader_source
         )?;
         trace!("Sprite shader compiled.");
-        let vbuf = ctx.create_buffer().ok_or("glCreateBuffer failed")?;
-        let vao = ctx.create_vertex_array()
-            .ok_or("glCreateVertexArray failed")?;
+        // Batches are streamed every frame into a ring buffer.
+        let vertices = DynamicPrimitive::new(gl, DrawArrayMode::TriangleList, BufferUsage::Stream, 6 * 256)?;
+        shader.enable_vertex_attribute(&vertices);
 
-        Ok(Sprite { gl: gl.clone(), shader, vbuf, vao, screen_size })
+        Ok(Sprite { gl: gl.clone(), shader, vertices, screen_size })
     }
 
     // Transform matrix to normalize destination positions from
@@ -66,7 +64,7 @@ impl Sprite {
         )
     }
 
-    pub fn draw(&self, batch: SpriteBatch) {
+    pub fn draw(&mut self, batch: SpriteBatch) -> Result<(), Error> {
         let ctx = self.gl.context();
         let transform = Self::normalizer(self.screen_size);
 
@@ -74,11 +72,7 @@ impl Sprite {
         ctx.enable(WebGl2RenderingContext::CULL_FACE);
 
         for (tex, _src, verts) in batch.commands {
-            let verts = verts.as_slice();
-            let verts = UVMappedSliceTriangleList(verts);
-
-            let obj = EphemeralPrimitive::transfer(&self.gl, verts, &self.vao, &self.vbuf);
-            self.shader.enable_vertex_attribute(&obj);
+            self.vertices.stream(&verts)?;
 
             ctx.bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(&tex));
             ctx.active_texture(WebGl2RenderingContext::TEXTURE0); 
@@ -86,15 +80,16 @@ impl Sprite {
 
             self.shader.set_uniform_model_view_perspective(&transform);
             self.shader.set_uniform_texture(0);
-            self.shader.draw(&obj);
+            self.shader.draw(&self.vertices);
             self.shader.disable();
             ctx.bind_texture(WebGl2RenderingContext::TEXTURE_2D, None);
         }
-    } 
+        Ok(())
+    }
 }
 
 pub struct SpriteBatch {
-    commands: Vec<(WebGlTexture, Vector4, Vec<(Vector3, Vector2)>)>
+    commands: Vec<(WebGlTexture, Vector4, Vec<UVMappedVertex>)>
 }
 
 impl SpriteBatch {
@@ -104,7 +99,7 @@ impl SpriteBatch {
         }
     }
 
-    fn push_vertices(v: &mut Vec<(Vector3, Vector2)>, source: Vector4, destination: Rectangle) {
+    fn push_vertices(v: &mut Vec<UVMappedVertex>, source: Vector4, destination: RectF) {
         // destination (Screen Coordination)
         // x-----------------*------>
         // |(x, y)           |(x + w, y)
@@ -126,31 +121,38 @@ impl SpriteBatch {
         // |(u1, v1)         |
         // x-----------------+----->
         //
-        let vec4!(x, y, w, h) = destination.into();
-        let dtl = vec2!(x, y); // (0, 0) goes to (-1, 1); top left corner
-        let dtr = vec2!(x + w, y); // (w, 0) goes to (1, 1); top right corner
-        let dbl = vec2!(x, y + h); // (0, h) goes to (-1, -1); bottom left corner
-        let dbr = vec2!(x + w, y + h); // (w, h) goes to (1, -1); bottom right corner
+        let destination: Vector4 = destination.into();
+        let origin = destination.xy();
+        let vec2!(w, h) = destination.zw();
+        let dtl = origin; // (0, 0) goes to (-1, 1); top left corner
+        let dtr = origin + vec2!(w, 0.0); // (w, 0) goes to (1, 1); top right corner
+        let dbl = origin + vec2!(0.0, h); // (0, h) goes to (-1, -1); bottom left corner
+        let dbr = origin + vec2!(w, h); // (w, h) goes to (1, -1); bottom right corner
         
-        let vec4!(u1, v1, u2, v2) = source;
-        let stl = vec2!(u1, v2);
-        let str = vec2!(u2, v2);
-        let sbl = vec2!(u1, v1);
-        let sbr = vec2!(u2, v1);
+        let sbl = source.xy(); // (u1, v1)
+        let str = source.zw(); // (u2, v2)
+        let stl = vec2!(sbl.x, str.y);
+        let sbr = vec2!(str.x, sbl.y);
 
         // we are going to invert y-position (see normalizer()),
         // so rendered rectangle will face backward.
         // Needs to place vertices in reverse-clockwise order.
-        v.push((dtl.into(), stl));
-        v.push((dbl.into(), sbl));
-        v.push((dtr.into(), str));
-
-        v.push((dbr.into(), sbr));
-        v.push((dtr.into(), str));
-        v.push((dbl.into(), sbl));
+        let vertex = |position: Vector2, texture_coordination| UVMappedVertex {
+            position: position.into(),
+            texture_coordination,
+        };
+        v.push(vertex(dtl, stl));
+        v.push(vertex(dbl, sbl));
+        v.push(vertex(dtr, str));
+
+        v.push(vertex(dbr, sbr));
+        v.push(vertex(dtr, str));
+        v.push(vertex(dbl, sbl));
     }
 
-    pub fn add(&mut self, texture: &WebGlTexture, source: Vector4, destination: Rectangle) {
+    pub fn add<Destination>(&mut self, texture: &WebGlTexture, source: Vector4, destination: Destination)
+        where Destination: Into<RectF> {
+        let destination = destination.into();
         if let Some((tex, src, _)) = self.commands.last() {
             if texture == tex && &source == src {
                 if let Some((_, _, verts)) = self.commands.last_mut() {
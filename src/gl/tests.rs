@@ -0,0 +1,899 @@
+use std::f32::consts::PI;
+use std::mem::size_of;
+
+use crate::{assert_approx_eq, gl::{gltf::{AlphaMode, Gltf, GltfSampler, GltfTextureRef}, mesh::{Mesh, MeshVertex}, obj::{parse_mtl, parse_obj}, optimize::{average_cache_miss_ratio, optimize_vertex_cache, optimize_vertex_fetch, stripify, unstripify, weld}, cast_slice, Half, check_draw_range, check_indices, check_stream_attributes, grown_capacity, ring_placement, updated_range, ColoredVertex, ComponentType, InstanceMatrix, DrawArrayMode, SliceLineLoop, SliceLineStrip, SliceLines, SlicePoints, SliceTriangleFan, SliceTriangleList, SliceTriangleStrip, TriangleStrip, UVMappedVertex, Vertex, VertexAttribute, VertexAttributeArray}, math::{Aabb, Color, Matrix4, Vector2, Vector3}, vec2, vec3, vec4};
+
+fn float(name: &'static str, offset: usize, size: usize, stride: usize) -> VertexAttribute {
+  VertexAttribute { name, offset, size, stride, component_type: ComponentType::Float, normalized: false, integer: false }
+}
+
+#[test]
+fn test_uv_mapped_vertex_layout() {
+  assert_eq!(size_of::<UVMappedVertex>(), 20);
+  let expected = [float("position", 0, 3, 20), float("textureCoord", 12, 2, 20)];
+  assert_eq!(UVMappedVertex::ATTRIBUTES, &expected);
+  assert_eq!(SliceTriangleStrip::<UVMappedVertex>::ATTRIBUTES, &expected);
+  assert_eq!(SliceTriangleList::<UVMappedVertex>::ATTRIBUTES, &expected);
+}
+
+#[test]
+fn test_colored_vertex_layout() {
+  assert_eq!(size_of::<ColoredVertex>(), 28);
+  assert_eq!(ColoredVertex::ATTRIBUTES, &[float("position", 0, 3, 28), float("color", 12, 4, 28)]);
+}
+
+#[derive(Clone, Copy, Vertex)]
+#[repr(C)]
+struct SkinnedVertex {
+  position: Vector3,
+  vertex_normal: Vector3,
+  uv: Vector2,
+  #[vertex(normalized)]
+  color: [u8; 4],
+  #[vertex(name = "joints", integer)]
+  joint_indices: [u16; 4],
+  weight: f32,
+}
+
+#[test]
+fn test_derived_vertex_layout() {
+  assert_eq!(size_of::<SkinnedVertex>(), 48);
+  assert_eq!(SkinnedVertex::ATTRIBUTES, &[
+    float("position", 0, 3, 48),
+    float("vertexNormal", 12, 3, 48),
+    float("uv", 24, 2, 48),
+    VertexAttribute { name: "color", offset: 32, size: 4, stride: 48, component_type: ComponentType::UnsignedByte, normalized: true, integer: false },
+    VertexAttribute { name: "joints", offset: 36, size: 4, stride: 48, component_type: ComponentType::UnsignedShort, normalized: false, integer: true },
+    float("weight", 44, 1, 48),
+  ]);
+}
+
+#[derive(Clone, Copy, Vertex)]
+#[repr(C)]
+struct Instance {
+  model: InstanceMatrix,
+  tint: Color,
+}
+
+#[test]
+fn test_attribute_slots() {
+  assert_eq!(Instance::ATTRIBUTES, &[float("model", 0, 16, 80), float("tint", 64, 4, 80)]);
+
+  let model = Instance::ATTRIBUTES[0];
+  let slots: Vec<_> = model.slots().collect();
+  assert_eq!(slots, vec![
+    float("model", 0, 4, 80),
+    float("model", 16, 4, 80),
+    float("model", 32, 4, 80),
+    float("model", 48, 4, 80),
+  ]);
+
+  let position = float("position", 4, 3, 20);
+  assert_eq!(position.slots().collect::<Vec<_>>(), vec![position]);
+
+  // Each slot holds a column, as a GLSL mat4 attribute expects, so the shader
+  // gets the same product as on the CPU.
+  let matrix = Matrix4::translation(1.0, 2.0, 3.0) * Matrix4::rotation(0.5, 0.25, 0.0);
+  let instance = Instance { model: matrix.into(), tint: Color::new(1.0, 1.0, 1.0, 1.0) };
+  assert_eq!(instance.model.matrix(), matrix);
+  let floats: &[f32] = cast_slice(std::slice::from_ref(&instance));
+  let columns: Vec<&[f32]> = slots.iter().map(|slot| &floats[slot.offset / 4..][..4]).collect();
+  assert_eq!(columns[3], &[1.0, 2.0, 3.0, 1.0]);
+  let v = [0.5, -1.0, 2.0, 1.0];
+  let product: Vec<f32> = (0..4).map(|row| (0..4).map(|n| columns[n][row] * v[n]).sum()).collect();
+  assert_approx_eq!(vec4!(product[0], product[1], product[2], product[3]), matrix * vec4!(v[0], v[1], v[2], v[3]));
+}
+
+#[derive(Clone, Copy, Vertex)]
+#[repr(C)]
+struct PackedVertex {
+  position: [Half; 4],
+  #[vertex(normalized)]
+  normal: [i8; 4],
+  #[vertex(normalized)]
+  uv: [u16; 2],
+}
+
+#[test]
+fn test_packed_vertex_layout() {
+  assert_eq!(size_of::<PackedVertex>(), 16);
+  let attribute = |name, offset, size, component_type| VertexAttribute {
+    name, offset, size, stride: 16, component_type, normalized: component_type != ComponentType::HalfFloat, integer: false,
+  };
+  assert_eq!(PackedVertex::ATTRIBUTES, &[
+    attribute("position", 0, 4, ComponentType::HalfFloat),
+    attribute("normal", 8, 4, ComponentType::Byte),
+    attribute("uv", 12, 2, ComponentType::UnsignedShort),
+  ]);
+}
+
+#[test]
+fn test_half_conversion() {
+  let exact = [0.0, 1.0, -2.0, 0.5, 65504.0, -0.000061035156, 0.000000059604645];
+  for value in exact {
+    assert_eq!(Half::from_f32(value).to_f32(), value);
+  }
+  assert_eq!(Half::from_f32(1.0), Half(0x3c00));
+  assert_eq!(Half::from_f32(-0.0), Half(0x8000));
+  assert_eq!(Half::from_f32(65520.0), Half(0x7c00));
+  assert_eq!(Half::from_f32(f32::NEG_INFINITY), Half(0xfc00));
+  assert!(Half::from_f32(f32::NAN).to_f32().is_nan());
+  assert_eq!(Half::from_f32(1e-9), Half(0));
+
+  // Ties round to even.
+  assert_eq!(Half::from_f32(1.0 + 1.0 / 2048.0), Half(0x3c00));
+  assert_eq!(Half::from_f32(1.0 + 3.0 / 2048.0), Half(0x3c02));
+  // Rounding carries into the exponent.
+  assert_eq!(Half::from_f32(2.0 - 1.0 / 4096.0), Half(0x4000));
+
+  let mut value = -100.0f32;
+  while value < 100.0 {
+    let half = Half::from_f32(value).to_f32();
+    assert!((half - value).abs() <= value.abs() / 1024.0, "{} -> {}", value, half);
+    value += 0.37;
+  }
+}
+
+#[test]
+fn test_as_bytes() {
+  let vertices = [
+    UVMappedVertex::from((vec3!(1.0, 2.0, 3.0), vec2!(4.0, 5.0))),
+    UVMappedVertex::from((vec3!(6.0, 7.0, 8.0), vec2!(9.0, 10.0))),
+  ];
+  let data = SliceTriangleList(&vertices);
+  let floats: &[f32] = cast_slice(data.as_bytes());
+  assert_eq!(floats, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0]);
+  assert_eq!(data.vertex_count(), 2);
+
+  let vertices = [ColoredVertex::from((vec3!(1.0, 2.0, 3.0), vec4!(4.0, 5.0, 6.0, 7.0)))];
+  let data = SliceTriangleStrip(&vertices);
+  let floats: &[f32] = cast_slice(data.as_bytes());
+  assert_eq!(floats, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0]);
+  assert!(SliceTriangleList::<UVMappedVertex>(&[]).as_bytes().is_empty());
+
+  let strip = TriangleStrip { vertices: vec![1.0, 2.0, 3.0] };
+  assert_eq!(strip.as_bytes().len(), 12);
+  assert_eq!(TriangleStrip::ATTRIBUTES, &[float("position", 0, 3, 0)]);
+}
+
+#[test]
+fn test_slice_draw_array_modes() {
+  assert_eq!(SlicePoints::<ColoredVertex>::DRAW_ARRAY_MODE, DrawArrayMode::Points);
+  assert_eq!(SliceLines::<ColoredVertex>::DRAW_ARRAY_MODE, DrawArrayMode::Lines);
+  assert_eq!(SliceLineStrip::<ColoredVertex>::DRAW_ARRAY_MODE, DrawArrayMode::LineStrip);
+  assert_eq!(SliceLineLoop::<ColoredVertex>::DRAW_ARRAY_MODE, DrawArrayMode::LineLoop);
+  assert_eq!(SliceTriangleStrip::<ColoredVertex>::DRAW_ARRAY_MODE, DrawArrayMode::TriangleStrip);
+  assert_eq!(SliceTriangleList::<ColoredVertex>::DRAW_ARRAY_MODE, DrawArrayMode::TriangleList);
+  assert_eq!(SliceTriangleFan::<ColoredVertex>::DRAW_ARRAY_MODE, DrawArrayMode::TriangleFan);
+
+  let vertices = [ColoredVertex::from((vec3!(0.0, 0.0, 0.0), vec4!(1.0, 1.0, 1.0, 1.0))); 5];
+  assert_eq!(SliceLineLoop(&vertices).vertex_count(), 5);
+  assert_eq!(SlicePoints::<ColoredVertex>::ATTRIBUTES, ColoredVertex::ATTRIBUTES);
+}
+
+#[test]
+fn test_check_indices() {
+  assert!(check_indices(&[0u8, 1, 2], 3).is_ok());
+  assert!(check_indices::<u16>(&[], 0).is_ok());
+  assert!(check_indices(&[0u32, 3, 1], 3).is_err());
+  assert!(check_indices(&[0u16], 0).is_err());
+}
+
+#[test]
+fn test_check_draw_range() {
+  assert!(check_draw_range(0, 36, 36).is_ok());
+  assert!(check_draw_range(18, 18, 36).is_ok());
+  assert!(check_draw_range(36, 0, 36).is_ok());
+  assert_eq!(check_draw_range(30, 12, 36).unwrap_err().to_string(), "range 30..42 is out of bounds for 36 elements");
+  assert!(check_draw_range(-1, 3, 36).is_err());
+  assert!(check_draw_range(0, -3, 36).is_err());
+  assert!(check_draw_range(1, i32::MAX, 36).is_err());
+}
+
+#[test]
+fn test_check_stream_attributes() {
+  #[derive(Clone, Copy, Vertex)]
+  #[repr(C)]
+  struct Tint {
+    color: Color,
+  }
+
+  let streams = [UVMappedVertex::ATTRIBUTES];
+  assert!(check_stream_attributes(&streams, Tint::ATTRIBUTES).is_ok());
+  assert_eq!(
+    check_stream_attributes(&streams, ColoredVertex::ATTRIBUTES).unwrap_err().to_string(),
+    "attribute position is already in another stream");
+  assert!(check_stream_attributes(&[UVMappedVertex::ATTRIBUTES, Tint::ATTRIBUTES], Tint::ATTRIBUTES).is_err());
+}
+
+#[test]
+fn test_grown_capacity() {
+  assert_eq!(grown_capacity(100, 80), 100);
+  assert_eq!(grown_capacity(100, 100), 100);
+  assert_eq!(grown_capacity(0, 1), 64);
+  assert_eq!(grown_capacity(100, 101), 256);
+  assert_eq!(grown_capacity(128, 1000), 1024);
+}
+
+#[test]
+fn test_updated_range() {
+  // Writing into an empty primitive draws exactly what was written.
+  assert_eq!(updated_range(0, 0, 5, 3).unwrap(), (5, 3));
+  assert_eq!(updated_range(0, 4, 2, 4).unwrap(), (0, 6));
+  assert_eq!(updated_range(4, 4, 0, 4).unwrap(), (0, 8));
+  assert_eq!(updated_range(4, 4, 8, 2).unwrap(), (4, 6));
+  assert_eq!(updated_range(4, 4, 5, 0).unwrap(), (4, 4));
+  assert_eq!(
+    updated_range(0, 4, 6, 2).unwrap_err().to_string(),
+    "update of vertices 6..8 is not adjacent to the drawn range 0..4");
+  assert!(updated_range(6, 2, 0, 4).is_err());
+}
+
+#[test]
+fn test_ring_placement() {
+  assert_eq!(ring_placement(0, 10, 4), (0, false));
+  assert_eq!(ring_placement(4, 10, 6), (4, false));
+  assert_eq!(ring_placement(8, 10, 4), (0, true));
+  assert_eq!(ring_placement(10, 10, 1), (0, true));
+}
+
+#[test]
+#[should_panic]
+fn test_cast_slice_size_mismatch() {
+  let _: &[f32] = cast_slice(&[0u8, 1, 2]);
+}
+
+// Checks the invariants every generated mesh shares and returns its volume.
+fn check_mesh(mesh: &Mesh) -> f32 {
+  check_mesh_up_to(mesh, 1.0)
+}
+
+// Icosphere triangles crossing the texture seam continue past s = 1, by less
+// than half a turn.
+fn check_icosphere(mesh: &Mesh) -> f32 {
+  check_mesh_up_to(mesh, 1.5)
+}
+
+// Checks indices, normals, winding and texture coordinates, where s may go up
+// to max_s. Returns the enclosed volume.
+fn check_mesh_up_to(mesh: &Mesh, max_s: f32) -> f32 {
+  assert_eq!(mesh.indices.len() % 3, 0);
+  assert!(mesh.indices.iter().all(|&i| (i as usize) < mesh.vertices.len()));
+
+  for v in &mesh.vertices {
+    assert!((v.normal.length() - 1.0).abs() < 1e-4, "normal {:?} is not unit length", v.normal);
+    let vec2!(s, t) = v.texture_coordination;
+    assert!((0.0..=max_s).contains(&s) && (0.0..=1.0).contains(&t), "texture coordinate {:?} is out of range", v.texture_coordination);
+  }
+
+  let mut volume = 0.0;
+  for triangle in mesh.indices.chunks(3) {
+    let [a, b, c] = [0, 1, 2].map(|n| mesh.vertices[triangle[n] as usize]);
+    let face = (b.position - a.position).cross(c.position - a.position);
+    if face.length() > 1e-6 {
+      // Counter-clockwise seen from outside: face normal agrees with vertex normals.
+      let normal = a.normal + b.normal + c.normal;
+      assert!(face.dot(normal) > 0.0, "triangle {:?} is wound inwards", triangle);
+    }
+    volume += a.position.dot(b.position.cross(c.position)) / 6.0;
+  }
+  volume
+}
+
+#[test]
+fn test_mesh_cube() {
+  let mesh = Mesh::cube(2.0);
+  assert_eq!(mesh.vertices.len(), 24);
+  assert_eq!(mesh.triangle_count(), 12);
+  assert_approx_eq!(check_mesh(&mesh), 8.0);
+  assert!(mesh.vertices.iter().all(|v| v.position.x.abs() == 1.0 || v.position.y.abs() == 1.0 || v.position.z.abs() == 1.0));
+}
+
+#[test]
+fn test_mesh_spheres() {
+  let sphere_volume = 4.0 / 3.0 * PI * 8.0;
+
+  let mesh = Mesh::uv_sphere(2.0, 64, 32);
+  assert_eq!(mesh.vertices.len(), 65 * 33);
+  assert_eq!(mesh.triangle_count(), 64 * 32 * 2);
+  assert!((check_mesh(&mesh) - sphere_volume).abs() / sphere_volume < 0.01);
+  assert!(mesh.vertices.iter().all(|v| (v.position.length() - 2.0).abs() < 1e-5));
+
+  let mesh = Mesh::icosphere(2.0, 0);
+  assert_eq!(mesh.vertices.len(), 12 + 3);
+  assert_eq!(mesh.triangle_count(), 20);
+  check_icosphere(&mesh);
+
+  // 642 positions, plus copies along the texture seam and at the poles.
+  let mesh = Mesh::icosphere(2.0, 3);
+  assert_eq!(mesh.vertices.len(), 674);
+  assert_eq!(mesh.triangle_count(), 1280);
+  assert!((check_icosphere(&mesh) - sphere_volume).abs() / sphere_volume < 0.01);
+  assert!(mesh.vertices.iter().all(|v| (v.position.length() - 2.0).abs() < 1e-5));
+}
+
+#[test]
+fn test_icosphere_texture_seam() {
+  for subdivisions in 0..4 {
+    let mesh = Mesh::icosphere(1.0, subdivisions);
+    for triangle in mesh.indices.chunks(3) {
+      let us = [0, 1, 2].map(|n| mesh.vertices[triangle[n] as usize].texture_coordination.x);
+      let span = us.iter().fold(0.0f32, |a, &b| a.max(b)) - us.iter().fold(2.0f32, |a, &b| a.min(b));
+      assert!(span <= 0.5, "triangle {:?} spans {} in u", triangle, span);
+    }
+  }
+
+  // Pole vertices are not shared and sit at the middle of their triangle in u.
+  let mesh = Mesh::icosphere(1.0, 2);
+  let poles: Vec<_> = mesh.indices.chunks(3).filter_map(|triangle| {
+    let corners = [0, 1, 2].map(|n| mesh.vertices[triangle[n] as usize]);
+    let pole = corners.iter().position(|v| v.position.y.abs() > 0.9999)?;
+    let others: Vec<_> = (0..3).filter(|&n| n != pole).map(|n| corners[n].texture_coordination.x).collect();
+    assert_approx_eq!(corners[pole].texture_coordination.x, (others[0] + others[1]) / 2.0);
+    Some(triangle[pole])
+  }).collect();
+  assert_eq!(poles.len(), 12);
+  let mut unique = poles.clone();
+  unique.sort();
+  unique.dedup();
+  assert_eq!(unique.len(), poles.len());
+}
+
+#[test]
+fn test_mesh_grid() {
+  let mesh = Mesh::grid(4.0, 2.0, 4, 2);
+  assert_eq!(mesh.vertices.len(), 15);
+  assert_eq!(mesh.triangle_count(), 16);
+  check_mesh(&mesh);
+  let bounds = Aabb::from_points(mesh.vertices.iter().map(|v| v.position)).unwrap();
+  assert_eq!(bounds, Aabb::new(vec3!(-2.0, 0.0, -1.0), vec3!(2.0, 0.0, 1.0)));
+  assert_eq!(Mesh::plane(1.0, 1.0).triangle_count(), 2);
+}
+
+#[test]
+fn test_mesh_solids_of_revolution() {
+  let relative_error = |actual: f32, expected: f32| (actual - expected).abs() / expected;
+
+  let mesh = Mesh::cylinder(1.0, 2.0, 128);
+  assert!(relative_error(check_mesh(&mesh), 2.0 * PI) < 0.01);
+
+  let mesh = Mesh::cone(1.0, 3.0, 128);
+  assert!(relative_error(check_mesh(&mesh), PI) < 0.01);
+  let apex = mesh.vertices.iter().map(|v| v.position.y).fold(f32::MIN, f32::max);
+  assert_approx_eq!(apex, 1.5);
+
+  let mesh = Mesh::torus(2.0, 0.5, 128, 64);
+  assert!(relative_error(check_mesh(&mesh), 2.0 * PI * PI * 2.0 * 0.25) < 0.01);
+
+  let mesh = Mesh::capsule(1.0, 2.0, 128, 32);
+  assert!(relative_error(check_mesh(&mesh), 2.0 * PI + 4.0 / 3.0 * PI) < 0.01);
+  let bounds = Aabb::from_points(mesh.vertices.iter().map(|v| v.position)).unwrap();
+  assert_approx_eq!(bounds.max.y, 2.0);
+  assert_approx_eq!(bounds.min.y, -2.0);
+}
+
+fn without_normals(mut mesh: Mesh) -> Mesh {
+  for v in &mut mesh.vertices {
+    v.normal = vec3!(0.0, 0.0, 0.0);
+  }
+  mesh
+}
+
+#[test]
+fn test_compute_flat_normals() {
+  let mut mesh = without_normals(Mesh::icosphere(1.0, 1));
+  mesh.compute_flat_normals();
+  assert_eq!(mesh.vertices.len(), mesh.indices.len());
+  for triangle in mesh.indices.chunks(3) {
+    let [a, b, c] = [0, 1, 2].map(|n| mesh.vertices[triangle[n] as usize]);
+    let face = (b.position - a.position).cross(c.position - a.position).normalize();
+    assert_approx_eq!(a.normal, face);
+    assert_approx_eq!(b.normal, face);
+    assert_approx_eq!(c.normal, face);
+  }
+  check_icosphere(&mesh);
+}
+
+#[test]
+fn test_compute_smooth_normals() {
+  let mut mesh = without_normals(Mesh::icosphere(2.0, 2));
+  let vertex_count = mesh.vertices.len();
+  mesh.compute_smooth_normals();
+  assert_eq!(mesh.vertices.len(), vertex_count);
+  for v in &mesh.vertices {
+    assert!(v.normal.dot(v.position.normalize()) > 0.999);
+  }
+
+  // Split vertices on the seam get the same normal.
+  let mut mesh = without_normals(Mesh::uv_sphere(1.0, 16, 8));
+  mesh.compute_smooth_normals();
+  let seam: Vec<_> = mesh.vertices.iter().filter(|v| v.position.x == 0.0 && v.position.z > 0.0 && v.position.y.abs() < 0.9).collect();
+  assert!(!seam.is_empty());
+  for v in seam {
+    // Symmetric around the seam.
+    assert!(v.normal.x.abs() < 1e-5);
+    assert!(v.normal.dot(v.position.normalize()) > 0.99);
+  }
+}
+
+#[test]
+fn test_compute_normals_crease_angle() {
+  // Cube edges are 90 degrees, so they stay hard below that.
+  let corners = |mesh: &Mesh| mesh.indices.iter().map(|&i| mesh.vertices[i as usize]).collect::<Vec<_>>();
+  let mut mesh = Mesh::cube(1.0);
+  let flat = corners(&mesh);
+  mesh.compute_normals(PI / 3.0);
+  assert_eq!(mesh.vertices.len(), 24);
+  assert_eq!(corners(&mesh), flat);
+
+  mesh.compute_normals(PI * 2.0 / 3.0);
+  assert_eq!(mesh.vertices.len(), 24);
+  for v in &mesh.vertices {
+    assert_approx_eq!(v.normal, v.position.normalize());
+  }
+
+  // A cylinder keeps the rim hard but smooths the side.
+  let mut mesh = without_normals(Mesh::cylinder(1.0, 1.0, 32));
+  mesh.compute_normals(PI / 4.0);
+  for v in &mesh.vertices {
+    if v.normal.y.abs() < 0.5 {
+      assert_approx_eq!(v.normal, vec3!(v.position.x, 0.0, v.position.z).normalize());
+    } else {
+      assert_eq!(v.normal.x.abs() + v.normal.z.abs(), 0.0);
+    }
+  }
+}
+
+#[test]
+fn test_compute_tangents() {
+  let mesh = Mesh::grid(2.0, 2.0, 2, 2).compute_tangents();
+  assert_eq!(mesh.vertices.len(), 9);
+  for v in &mesh.vertices {
+    // u grows along +X and v along +Z, which is left-handed around +Y.
+    assert_approx_eq!(v.tangent, vec4!(1.0, 0.0, 0.0, -1.0));
+  }
+
+  let mesh = Mesh::uv_sphere(1.0, 32, 16).compute_tangents();
+  for v in &mesh.vertices {
+    let vec4!(x, y, z, w) = v.tangent;
+    let tangent = vec3!(x, y, z);
+    assert_approx_eq!(tangent.length(), 1.0);
+    assert!(tangent.dot(v.normal).abs() < 1e-4);
+    assert!(w == 1.0 || w == -1.0);
+  }
+  // Away from the poles the tangent follows increasing u around the Y axis.
+  for v in mesh.vertices.iter().filter(|v| v.position.y.abs() < 0.5) {
+    let around = vec3!(v.position.z, 0.0, -v.position.x).normalize();
+    assert!(vec3!(v.tangent.x, v.tangent.y, v.tangent.z).dot(around) > 0.99);
+  }
+}
+
+#[test]
+fn test_compute_tangents_mirrored() {
+  // Two triangles sharing the edge 0-1, the second with mirrored u.
+  let vertex = |x, y, u, v| MeshVertex { position: vec3!(x, y, 0.0), normal: vec3!(0.0, 0.0, 1.0), texture_coordination: vec2!(u, v) };
+  let mesh = Mesh {
+    vertices: vec![vertex(0.0, 0.0, 0.0, 0.0), vertex(0.0, 1.0, 0.0, 1.0), vertex(1.0, 0.0, 1.0, 0.0), vertex(-1.0, 0.0, 1.0, 0.0)],
+    indices: vec![0, 2, 1, 0, 1, 3],
+  };
+  let mesh = mesh.compute_tangents();
+  assert_eq!(mesh.vertices.len(), 6);
+  let tangents: Vec<_> = mesh.indices.iter().map(|&i| mesh.vertices[i as usize].tangent).collect();
+  for t in &tangents[..3] {
+    assert_approx_eq!(*t, vec4!(1.0, 0.0, 0.0, 1.0));
+  }
+  for t in &tangents[3..] {
+    assert_approx_eq!(*t, vec4!(-1.0, 0.0, 0.0, -1.0));
+  }
+}
+
+#[test]
+fn test_parse_obj() {
+  let model = parse_obj(include_str!("testdata/cube.obj")).unwrap();
+  assert_eq!(model.material_libraries, vec!["cube.mtl"]);
+  assert_eq!(model.mesh.vertices.len(), 24);
+  assert_eq!(model.mesh.triangle_count(), 12);
+  assert_approx_eq!(check_mesh(&model.mesh), 1.0);
+
+  let groups: Vec<_> = model.groups.iter()
+    .map(|g| (g.name.as_str(), g.material.as_deref(), g.first_index, g.index_count))
+    .collect();
+  assert_eq!(groups, vec![("Cube", Some("red"), 0, 18), ("Cube", Some("blue"), 18, 18)]);
+
+  // The first corner of the first face: `1/1/1`, with v flipped.
+  assert_eq!(model.mesh.vertices[0], MeshVertex {
+    position: vec3!(-0.5, -0.5, 0.5),
+    normal: vec3!(0.0, 0.0, 1.0),
+    texture_coordination: vec2!(0.0, 1.0),
+  });
+}
+
+#[test]
+fn test_parse_obj_without_normals() {
+  let source = "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 0 0 1\nf 1 3 2\nf 1 2 4\nf 1 4 3\nf 2 3 4\n";
+  let model = parse_obj(source).unwrap();
+  assert_eq!(model.groups.len(), 1);
+  assert_eq!(model.groups[0].index_count, 12);
+  assert_approx_eq!(check_mesh(&model.mesh), 1.0 / 6.0);
+}
+
+#[test]
+fn test_parse_obj_mixed_normals() {
+  // The first face has an authored normal; the others have none. The last face
+  // repeats positions of the second through other `v` statements and faces down.
+  let source = "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 0 0 1\nv 0 0 0\nv 1 0 0\nv 0 0 -1\nvn 1 0 0\n\
+    f 1//1 2//1 3//1\nf 1 4 2\nf 5 7 6\n";
+  let model = parse_obj(source).unwrap();
+  let normals: Vec<_> = model.mesh.indices.iter().map(|&i| model.mesh.vertices[i as usize].normal).collect();
+  assert_eq!(normals[..3], [vec3!(1.0, 0.0, 0.0); 3]);
+  for &normal in &normals[3..6] {
+    assert_approx_eq!(normal, vec3!(0.0, 1.0, 0.0));
+  }
+  for &normal in &normals[6..] {
+    assert_approx_eq!(normal, vec3!(0.0, -1.0, 0.0));
+  }
+}
+
+#[test]
+fn test_parse_obj_errors() {
+  let message = |source: &str| parse_obj(source).unwrap_err().to_string();
+  assert_eq!(message("v 0 0 0\nv 1 0 0\nf 1 2\n"), "line 3: a face needs at least 3 vertices");
+  assert_eq!(message("# comment\nv 0 0 0\nf 1 2 3\n"), "line 3: position index 2 is out of range");
+  assert_eq!(message("v 0 0 0\nf 0 1 1\n"), "line 2: position index 0 is out of range");
+  assert_eq!(message("v 0 zero 0\n"), "line 1: invalid y: zero");
+  assert_eq!(message("v 0 0\n"), "line 1: missing z");
+  assert_eq!(message("v 0 0 0\nf 1/1 1 1\n"), "line 2: texture coordinate index 1 is out of range");
+}
+
+#[test]
+fn test_parse_mtl() {
+  let materials = parse_mtl(include_str!("testdata/cube.mtl")).unwrap();
+  assert_eq!(materials.len(), 2);
+
+  let red = &materials[0];
+  assert_eq!(red.name, "red");
+  assert_eq!(red.ambient, Color::new(0.1, 0.1, 0.1, 1.0));
+  assert_eq!(red.diffuse, Color::new(1.0, 0.0, 0.0, 1.0));
+  assert_eq!(red.specular, Color::new(0.5, 0.5, 0.5, 1.0));
+  assert_eq!(red.shininess, 32.0);
+  assert_eq!(red.illumination, 2);
+  assert_eq!(red.diffuse_texture.as_deref(), Some("textures/red.png"));
+
+  let blue = &materials[1];
+  assert_eq!(blue.opacity, 0.75);
+  assert_eq!(blue.normal_texture.as_deref(), Some("textures/blue_normal.png"));
+  assert_eq!(blue.specular, Color::BLACK);
+
+  assert_eq!(parse_mtl("Kd 1 0 0\n").unwrap_err().to_string(), "line 1: Kd before newmtl");
+  assert_eq!(parse_mtl("newmtl a\n\nKd 1 x 0\n").unwrap_err().to_string(), "line 3: invalid color: x");
+}
+
+#[test]
+fn test_gltf_import() {
+  let gltf = Gltf::from_gltf(include_bytes!("testdata/triangle.gltf"), |uri| {
+    assert_eq!(uri, "texture.png");
+    Ok(b"png".to_vec())
+  }).unwrap();
+
+  assert_eq!(gltf.meshes[0].name.as_deref(), Some("triangle"));
+  let primitive = &gltf.meshes[0].primitives[0];
+  assert_eq!(primitive.draw_array_mode, DrawArrayMode::TriangleList);
+  assert_eq!(primitive.material, Some(0));
+  // Without normals the triangles are unindexed and flat shaded.
+  assert_eq!(primitive.indices, None);
+  let vertices = &primitive.vertices;
+  // The third position comes from the sparse accessor.
+  assert_eq!(vertices.iter().map(|v| v.position).collect::<Vec<_>>(),
+    vec![vec3!(0.0, 0.0, 0.0), vec3!(1.0, 0.0, 0.0), vec3!(0.0, 2.0, 0.0)]);
+  assert!(vertices.iter().all(|v| v.normal == vec3!(0.0, 0.0, 1.0)));
+  assert_eq!(vertices.iter().map(|v| v.texture_coordination).collect::<Vec<_>>(),
+    vec![vec2!(0.0, 0.0), vec2!(1.0, 0.0), vec2!(0.0, 1.0)]);
+  assert_eq!(vertices[1].color, Color::new(0.0, 1.0, 0.0, 1.0));
+
+  let material = &gltf.materials[0];
+  assert_eq!(material.base_color, Color::new(1.0, 0.5, 0.25, 1.0));
+  assert_eq!(material.base_color_texture, Some(GltfTextureRef { texture: 0, texture_coordination: 0 }));
+  assert_eq!(material.metallic, 0.0);
+  assert_eq!(material.roughness, 1.0);
+  assert_eq!(material.alpha_mode, AlphaMode::Mask(0.25));
+  assert!(material.double_sided);
+
+  assert_eq!(gltf.textures[0].image, Some(0));
+  assert_eq!(gltf.textures[0].sampler.mag_filter, Some(9729));
+  assert_eq!(gltf.textures[0].sampler.wrap_s, 10497);
+  assert_eq!(gltf.textures[0].sampler.min_filter_or_default(), 9987);
+  assert!(gltf.textures[0].sampler.uses_mipmaps());
+  let linear = GltfSampler { min_filter: Some(9729), ..gltf.textures[0].sampler };
+  assert!(!linear.uses_mipmaps());
+  assert_eq!(gltf.images[0].data, b"png");
+
+  assert_eq!(gltf.scene, vec![0]);
+  let child = &gltf.nodes[1];
+  assert_eq!(child.parent, Some(0));
+  assert_eq!(child.mesh, Some(0));
+  // Scaled, rotated a quarter turn around z, then moved by the parent.
+  assert_approx_eq!(child.world_transform * vec3!(1.0, 0.0, 0.0), vec3!(1.0, 2.0, 0.0));
+  // Matrices are column-major in the file.
+  assert_approx_eq!(gltf.nodes[2].world_transform * vec3!(0.0, 0.0, 0.0), vec3!(0.0, 0.0, 5.0));
+}
+
+fn glb(json: &str, bin: &[u8]) -> Vec<u8> {
+  let mut json = json.as_bytes().to_vec();
+  json.resize((json.len() + 3) / 4 * 4, b' ');
+  let mut bin = bin.to_vec();
+  bin.resize((bin.len() + 3) / 4 * 4, 0);
+
+  let mut glb = Vec::new();
+  glb.extend_from_slice(b"glTF");
+  glb.extend_from_slice(&2u32.to_le_bytes());
+  glb.extend_from_slice(&((12 + 8 + json.len() + 8 + bin.len()) as u32).to_le_bytes());
+  glb.extend_from_slice(&(json.len() as u32).to_le_bytes());
+  glb.extend_from_slice(b"JSON");
+  glb.extend_from_slice(&json);
+  glb.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+  glb.extend_from_slice(b"BIN\0");
+  glb.extend_from_slice(&bin);
+  glb
+}
+
+#[test]
+fn test_glb_import() {
+  let mut bin = Vec::new();
+  for v in [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0] {
+    bin.extend_from_slice(&v.to_le_bytes());
+  }
+  for i in [0u32, 1, 2] {
+    bin.extend_from_slice(&i.to_le_bytes());
+  }
+  // Normalized bytes, padded to a stride of 4.
+  bin.extend_from_slice(&[0, 0, 127, 0, 128, 0, 0, 0, 0, 127, 0, 0]);
+  bin.extend_from_slice(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]);
+  for w in [65535u16, 0, 0, 0, 32768, 32767, 0, 0, 0, 0, 0, 65535] {
+    bin.extend_from_slice(&w.to_le_bytes());
+  }
+  bin.extend_from_slice(&[1, 0, 0, 0]);
+  for v in [1.0f32, 0.0, 0.0, -1.0] {
+    bin.extend_from_slice(&v.to_le_bytes());
+  }
+
+  let json = r#"{
+    "asset": {"version": "2.0"},
+    "meshes": [{"primitives": [{
+      "attributes": {"POSITION": 0, "NORMAL": 2, "JOINTS_0": 3, "WEIGHTS_0": 4, "TANGENT": 5},
+      "indices": 1
+    }]}],
+    "accessors": [
+      {"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3"},
+      {"bufferView": 1, "componentType": 5125, "count": 3, "type": "SCALAR"},
+      {"bufferView": 2, "componentType": 5120, "normalized": true, "count": 3, "type": "VEC3"},
+      {"bufferView": 3, "componentType": 5121, "count": 3, "type": "VEC4"},
+      {"bufferView": 4, "componentType": 5123, "normalized": true, "count": 3, "type": "VEC4"},
+      {"componentType": 5126, "count": 3, "type": "VEC4", "sparse": {"count": 1,
+        "indices": {"bufferView": 5, "componentType": 5123},
+        "values": {"bufferView": 6}}}
+    ],
+    "bufferViews": [
+      {"buffer": 0, "byteOffset": 0, "byteLength": 36},
+      {"buffer": 0, "byteOffset": 36, "byteLength": 12},
+      {"buffer": 0, "byteOffset": 48, "byteLength": 12, "byteStride": 4},
+      {"buffer": 0, "byteOffset": 60, "byteLength": 12},
+      {"buffer": 0, "byteOffset": 72, "byteLength": 24},
+      {"buffer": 0, "byteOffset": 96, "byteLength": 2},
+      {"buffer": 0, "byteOffset": 100, "byteLength": 16}
+    ],
+    "buffers": [{"byteLength": 116}]
+  }"#;
+
+  let gltf = Gltf::from_glb(&glb(json, &bin), |uri| panic!("unexpected load of {}", uri)).unwrap();
+  let primitive = &gltf.meshes[0].primitives[0];
+  assert_eq!(primitive.indices, Some(vec![0, 1, 2]));
+
+  let vertices = &primitive.vertices;
+  assert_eq!(vertices.iter().map(|v| v.normal).collect::<Vec<_>>(),
+    vec![vec3!(0.0, 0.0, 1.0), vec3!(-1.0, 0.0, 0.0), vec3!(0.0, 1.0, 0.0)]);
+  assert_eq!(vertices[2].joints, [8, 9, 10, 11]);
+  assert_eq!(vertices[0].weights, vec4!(1.0, 0.0, 0.0, 0.0));
+  assert_approx_eq!(vertices[1].weights.x, 0.5);
+  // Only the sparse element is set, the rest stays zero.
+  assert_eq!(vertices[0].tangent, vec4!(0.0, 0.0, 0.0, 0.0));
+  assert_eq!(vertices[1].tangent, vec4!(1.0, 0.0, 0.0, -1.0));
+  assert!(gltf.scene.is_empty());
+}
+
+#[test]
+fn test_gltf_errors() {
+  let message = |json: &str| Gltf::from_gltf(json.as_bytes(), |_| Ok(vec![])).unwrap_err().to_string();
+  assert_eq!(message(r#"{"asset": {"version": "1.0"}}"#), "gltf: unsupported version 1.0");
+  assert_eq!(
+    message(r#"{"asset": {"version": "2.0"}, "extensionsRequired": ["KHR_draco_mesh_compression"]}"#),
+    "gltf: unsupported required extension KHR_draco_mesh_compression");
+  assert_eq!(
+    message(r#"{"asset": {"version": "2.0"}, "nodes": [{"children": [1]}, {"children": [0]}]}"#),
+    "gltf: node hierarchy contains a cycle");
+  assert_eq!(
+    message(r#"{"asset": {"version": "2.0"}, "nodes": [{"mesh": 0}]}"#),
+    "gltf: mesh 0 does not exist");
+  assert_eq!(
+    message(r#"{"asset": {"version": "2.0"}, "scenes": [{"nodes": [2]}], "nodes": [{}]}"#),
+    "gltf: node 2 does not exist");
+  assert_eq!(
+    message(r#"{"asset": {"version": "2.0"}, "textures": [{"source": 0}]}"#),
+    "gltf: image 0 does not exist");
+  assert_eq!(
+    message(r#"{"asset": {"version": "2.0"}, "materials": [{"normalTexture": {"index": 1}}], "textures": [{}]}"#),
+    "gltf: texture 1 does not exist");
+  assert_eq!(
+    message(r#"{"asset": {"version": "2.0"}, "meshes": [{"primitives": [{"attributes": {}, "material": 0}]}]}"#),
+    "gltf: material 0 does not exist");
+  assert_eq!(
+    message(r#"{"asset": {"version": "2.0"}, "buffers": [{"byteLength": 4, "uri": "data:,abc"}]}"#),
+    "gltf: only base64 data URIs are supported");
+  assert_eq!(
+    Gltf::from_glb(b"glTF\x01\0\0\0\x0c\0\0\0", |_| Ok(vec![])).unwrap_err().to_string(),
+    "glb: unsupported version 1");
+
+  // A VEC2 position would otherwise be read past its components.
+  let bin: Vec<u8> = [0.0f32, 0.0, 1.0, 0.0, 0.0, 1.0].iter().flat_map(|v| v.to_le_bytes()).collect();
+  let json = r#"{
+    "asset": {"version": "2.0"},
+    "meshes": [{"primitives": [{"attributes": {"POSITION": 0}}]}],
+    "accessors": [{"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC2"}],
+    "bufferViews": [{"buffer": 0, "byteLength": 24}],
+    "buffers": [{"byteLength": 24}]
+  }"#;
+  assert_eq!(
+    Gltf::from_glb(&glb(json, &bin), |_| Ok(vec![])).unwrap_err().to_string(),
+    "gltf: POSITION must be VEC3");
+  let json = json.replace(r#""POSITION": 0"#, r#""POSITION": 0, "WEIGHTS_0": 0"#).replace("VEC2", "VEC3").replace("24", "36");
+  let bin: Vec<u8> = bin.iter().chain(&[0; 12]).copied().collect();
+  assert_eq!(
+    Gltf::from_glb(&glb(&json, &bin), |_| Ok(vec![])).unwrap_err().to_string(),
+    "gltf: WEIGHTS_0 must be VEC4");
+
+  // Offsets and counts near usize::MAX must not overflow or allocate.
+  let bin = [0; 36];
+  let json = r#"{
+    "asset": {"version": "2.0"},
+    "meshes": [{"primitives": [{"attributes": {"POSITION": 0}}]}],
+    "accessors": [{"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3"}],
+    "bufferViews": [{"buffer": 0, "byteLength": 36}],
+    "buffers": [{"byteLength": 36}]
+  }"#;
+  assert!(Gltf::from_glb(&glb(json, &bin), |_| Ok(vec![])).is_ok());
+  let view_offset = json.replace(r#""byteLength": 36}],"#, r#""byteLength": 36, "byteOffset": 18446744073709551615}],"#);
+  assert_eq!(
+    Gltf::from_glb(&glb(&view_offset, &bin), |_| Ok(vec![])).unwrap_err().to_string(),
+    "gltf: buffer view 0 is out of range");
+  let accessor_offset = json.replace(r#""count": 3,"#, r#""count": 3, "byteOffset": 18446744073709551615,"#);
+  assert_eq!(
+    Gltf::from_glb(&glb(&accessor_offset, &bin), |_| Ok(vec![])).unwrap_err().to_string(),
+    "gltf: accessor exceeds buffer view 0");
+  let accessor_count = json.replace(r#""count": 3"#, r#""count": 4611686018427387903"#);
+  assert_eq!(
+    Gltf::from_glb(&glb(&accessor_count, &bin), |_| Ok(vec![])).unwrap_err().to_string(),
+    "gltf: accessor exceeds buffer view 0");
+  let zero_count = accessor_count.replace(r#""bufferView": 0, "#, "");
+  assert_eq!(
+    Gltf::from_glb(&glb(&zero_count, &bin), |_| Ok(vec![])).unwrap_err().to_string(),
+    "gltf: accessor 0 has too many elements");
+  let stride = json.replace(r#""byteLength": 36}],"#, r#""byteLength": 36, "byteStride": 4}],"#);
+  assert_eq!(
+    Gltf::from_glb(&glb(&stride, &bin), |_| Ok(vec![])).unwrap_err().to_string(),
+    "gltf: buffer view 0 stride is smaller than its elements");
+}
+
+// Triangles as a sorted list, each rotated to start at its smallest index so
+// that the winding is kept.
+fn canonical_triangles(indices: &[u32]) -> Vec<[u32; 3]> {
+  let mut triangles: Vec<[u32; 3]> = indices.chunks_exact(3).map(|t| {
+    let [a, b, c] = [t[0], t[1], t[2]];
+    if a <= b && a <= c { [a, b, c] } else if b <= c { [b, c, a] } else { [c, a, b] }
+  }).collect();
+  triangles.sort();
+  triangles
+}
+
+// Deterministically shuffled triangles of a grid.
+fn shuffled_grid(columns: u32, rows: u32) -> Mesh {
+  let mut mesh = Mesh::grid(1.0, 1.0, columns, rows);
+  let mut triangles: Vec<[u32; 3]> = mesh.indices.chunks_exact(3).map(|t| [t[0], t[1], t[2]]).collect();
+  let mut seed = 12345u32;
+  for n in (1..triangles.len()).rev() {
+    seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+    triangles.swap(n, (seed >> 8) as usize % (n + 1));
+  }
+  mesh.indices = triangles.concat();
+  mesh
+}
+
+#[test]
+fn test_weld() {
+  let mut mesh = Mesh::cube(1.0);
+  // Split every triangle, then weld it back.
+  mesh.vertices = mesh.indices.iter().map(|&i| mesh.vertices[i as usize]).collect();
+  mesh.indices = (0..mesh.vertices.len() as u32).collect();
+  let (vertices, indices) = weld(&mesh.vertices, &mesh.indices);
+  assert_eq!(vertices.len(), 24);
+  assert_eq!(indices.len(), 36);
+  for (&welded, &split) in indices.iter().zip(&mesh.indices) {
+    assert_eq!(vertices[welded as usize], mesh.vertices[split as usize]);
+  }
+
+  // Unused vertices are dropped.
+  let (vertices, indices) = weld(&[1.0f32, 2.0, 1.0, 3.0], &[2, 1, 0]);
+  assert_eq!(vertices, vec![1.0, 2.0]);
+  assert_eq!(indices, vec![0, 1, 0]);
+}
+
+#[test]
+fn test_optimize_vertex_cache() {
+  let mesh = shuffled_grid(32, 32);
+  let optimized = optimize_vertex_cache(&mesh.indices);
+  assert_eq!(canonical_triangles(&optimized), canonical_triangles(&mesh.indices));
+
+  let before = average_cache_miss_ratio(&mesh.indices, 16);
+  let after = average_cache_miss_ratio(&optimized, 16);
+  assert!(before > 1.5, "{}", before);
+  assert!(after < 0.8, "{}", after);
+
+  let (vertices, indices) = optimize_vertex_fetch(&mesh.vertices, &optimized);
+  assert_eq!(vertices.len(), mesh.vertices.len());
+  // Each new vertex is first used right after the previous one.
+  let mut next = 0;
+  for &i in &indices {
+    assert!(i <= next);
+    next = next.max(i + 1);
+  }
+  for (&new, &old) in indices.iter().zip(&optimized) {
+    assert_eq!(vertices[new as usize], mesh.vertices[old as usize]);
+  }
+}
+
+#[test]
+fn test_optimize_vertex_cache_soup() {
+  // No triangle shares a vertex, so every triangle restarts the search. That
+  // must not rescan the mesh each time, which made this quadratic.
+  let soup: Vec<u32> = (0..120000).collect();
+  assert_eq!(optimize_vertex_cache(&soup), soup);
+
+  // Separate grids are still emitted one after the other.
+  let mut mesh = shuffled_grid(4, 4);
+  let offset = mesh.vertices.len() as u32;
+  let second: Vec<u32> = mesh.indices.iter().map(|&i| i + offset).collect();
+  mesh.indices.extend(second);
+  let optimized = optimize_vertex_cache(&mesh.indices);
+  assert_eq!(canonical_triangles(&optimized), canonical_triangles(&mesh.indices));
+  let half = optimized.len() / 2;
+  assert!(optimized[..half].iter().all(|&i| i < offset) || optimized[..half].iter().all(|&i| i >= offset));
+}
+
+#[test]
+fn test_mesh_optimize() {
+  let mut mesh = shuffled_grid(8, 8);
+  let volume = check_mesh(&mesh);
+  mesh.optimize();
+  assert_eq!(mesh.vertices.len(), 81);
+  assert_eq!(mesh.triangle_count(), 128);
+  assert_approx_eq!(check_mesh(&mesh), volume);
+}
+
+#[test]
+fn test_stripify() {
+  // Four triangles of a band become one strip without degenerate triangles.
+  let strip = stripify(&[0, 1, 2, 2, 1, 3, 2, 3, 4, 4, 3, 5]);
+  assert_eq!(strip.len(), 6);
+  assert_eq!(canonical_triangles(&unstripify(&strip)), canonical_triangles(&[0, 1, 2, 2, 1, 3, 2, 3, 4, 4, 3, 5]));
+
+  for mesh in [Mesh::cube(1.0), Mesh::uv_sphere(1.0, 12, 6), Mesh::torus(1.0, 0.25, 12, 8), shuffled_grid(6, 6)] {
+    let strip = stripify(&mesh.indices);
+    assert_eq!(canonical_triangles(&unstripify(&strip)), canonical_triangles(&mesh.indices));
+  }
+
+  // A grid is mostly strips; far fewer indices than the list.
+  let mut grid = Mesh::grid(1.0, 1.0, 16, 16);
+  grid.optimize();
+  assert!(stripify(&grid.indices).len() < grid.indices.len() * 2 / 3);
+
+  // Degenerate triangles are dropped.
+  assert_eq!(stripify(&[0, 0, 1]), Vec::<u32>::new());
+}
@@ -0,0 +1,257 @@
+use std::mem::{align_of, size_of, size_of_val};
+
+use crate::math::{Color, Matrix4, Vector2, Vector3, Vector4};
+
+use super::{ComponentType, IndexType, VertexAttribute};
+
+// Derives `Pod` and `Vertex` for a `#[repr(C)]` struct without padding.
+// See the app-derive crate for the field attributes.
+pub use app_derive::Vertex;
+
+/// Plain old data that can be viewed as raw bytes and uploaded to GPU buffers.
+///
+/// # Safety
+///
+/// Implementors must be `#[repr(C)]` (or primitives), have no padding bytes,
+/// and every bit pattern must be a valid value.
+pub unsafe trait Pod: Copy + 'static {}
+
+unsafe impl Pod for u8 {}
+unsafe impl Pod for u16 {}
+unsafe impl Pod for u32 {}
+unsafe impl Pod for i8 {}
+unsafe impl Pod for i16 {}
+unsafe impl Pod for i32 {}
+unsafe impl Pod for f32 {}
+unsafe impl Pod for Vector2 {}
+unsafe impl Pod for Vector3 {}
+unsafe impl Pod for Vector4 {}
+unsafe impl Pod for Color {}
+unsafe impl Pod for Matrix4 {}
+unsafe impl Pod for InstanceMatrix {}
+unsafe impl Pod for Half {}
+unsafe impl<T: Pod, const N: usize> Pod for [T; N] {}
+
+// IEEE 754 binary16, stored as raw bits. Half the size of f32 with about
+// 3 significant decimal digits; enough for normals, UVs and colors.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[repr(transparent)]
+pub struct Half(pub u16);
+
+#[allow(dead_code)]
+impl Half {
+    // Rounds to the nearest representable value, ties to even.
+    // Values out of range become infinity.
+    pub fn from_f32(value: f32) -> Half {
+        let bits = value.to_bits();
+        let sign = ((bits >> 16) & 0x8000) as u16;
+        let exponent = ((bits >> 23) & 0xff) as i32;
+        let mantissa = bits & 0x7f_ffff;
+
+        if exponent == 0xff {
+            // Infinity or NaN. Keep NaN quiet and non-zero.
+            let nan = if mantissa != 0 { 0x200 } else { 0 };
+            return Half(sign | 0x7c00 | nan);
+        }
+
+        let exponent = exponent - 127 + 15;
+        if exponent >= 0x1f {
+            return Half(sign | 0x7c00);
+        }
+
+        // Subnormals shift the implicit leading bit into the mantissa.
+        let (bits, shift) = if exponent <= 0 {
+            if exponent < -10 {
+                return Half(sign);
+            }
+            (mantissa | 0x80_0000, (14 - exponent) as u32)
+        } else {
+            (((exponent as u32) << 23) | mantissa, 13)
+        };
+
+        let half = bits >> shift;
+        let rest = bits & ((1 << shift) - 1);
+        let halfway = 1 << (shift - 1);
+        let round_up = rest > halfway || (rest == halfway && half & 1 == 1);
+        // A carry out of the mantissa correctly bumps the exponent.
+        Half(sign | (half + round_up as u32) as u16)
+    }
+
+    pub fn to_f32(self) -> f32 {
+        let sign = ((self.0 & 0x8000) as u32) << 16;
+        let exponent = ((self.0 >> 10) & 0x1f) as u32;
+        let mantissa = (self.0 & 0x3ff) as u32;
+
+        match exponent {
+            0 => {
+                let value = mantissa as f32 / (1 << 24) as f32;
+                if sign != 0 { -value } else { value }
+            }
+            0x1f => f32::from_bits(sign | 0x7f80_0000 | (mantissa << 13)),
+            _ => f32::from_bits(sign | ((exponent + 127 - 15) << 23) | (mantissa << 13)),
+        }
+    }
+}
+
+impl From<f32> for Half {
+    fn from(value: f32) -> Self {
+        Half::from_f32(value)
+    }
+}
+
+impl From<Half> for f32 {
+    fn from(value: Half) -> Self {
+        value.to_f32()
+    }
+}
+
+// A vertex type whose fields map to shader attributes.
+// Usually implemented with `#[derive(Vertex)]`.
+pub trait Vertex: Pod {
+    const ATTRIBUTES: &'static [VertexAttribute];
+}
+
+// A type usable as a field of a vertex: `COMPONENT_COUNT` values of `COMPONENT_TYPE`.
+pub trait VertexComponent: Pod {
+    const COMPONENT_TYPE: ComponentType;
+    const COMPONENT_COUNT: usize;
+}
+
+macro_rules! vertex_component {
+    ($($t:ty => $component_type:ident, $count:expr;)*) => {
+        $(
+            impl VertexComponent for $t {
+                const COMPONENT_TYPE: ComponentType = ComponentType::$component_type;
+                const COMPONENT_COUNT: usize = $count;
+            }
+        )*
+    };
+}
+
+macro_rules! scalar_vertex_component {
+    ($($t:ty => $component_type:ident;)*) => {
+        vertex_component! { $($t => $component_type, 1;)* }
+        $(
+            impl<const N: usize> VertexComponent for [$t; N] {
+                const COMPONENT_TYPE: ComponentType = ComponentType::$component_type;
+                const COMPONENT_COUNT: usize = N;
+            }
+        )*
+    };
+}
+
+scalar_vertex_component! {
+    i8 => Byte;
+    u8 => UnsignedByte;
+    i16 => Short;
+    u16 => UnsignedShort;
+    i32 => Int;
+    u32 => UnsignedInt;
+    f32 => Float;
+    Half => HalfFloat;
+}
+
+vertex_component! {
+    Vector2 => Float, 2;
+    Vector3 => Float, 3;
+    Vector4 => Float, 4;
+    Color => Float, 4;
+}
+
+// A Matrix4 stored column by column, for per-instance transforms. It takes 4
+// attribute locations, and GLSL reads each one as a column of a mat4, so the
+// shader multiplies `model * position` just like with uniform matrices.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[repr(transparent)]
+pub struct InstanceMatrix(Matrix4);
+
+impl From<Matrix4> for InstanceMatrix {
+    fn from(matrix: Matrix4) -> Self {
+        InstanceMatrix(matrix.transpose())
+    }
+}
+
+#[allow(dead_code)]
+impl InstanceMatrix {
+    pub fn matrix(self) -> Matrix4 {
+        self.0.transpose()
+    }
+}
+
+vertex_component! {
+    InstanceMatrix => Float, 16;
+}
+
+// An integer type usable in an element array.
+pub trait Index: Pod {
+    const INDEX_TYPE: IndexType;
+
+    fn to_usize(self) -> usize;
+}
+
+impl Index for u8 {
+    const INDEX_TYPE: IndexType = IndexType::U8;
+
+    fn to_usize(self) -> usize {
+        self as usize
+    }
+}
+
+impl Index for u16 {
+    const INDEX_TYPE: IndexType = IndexType::U16;
+
+    fn to_usize(self) -> usize {
+        self as usize
+    }
+}
+
+impl Index for u32 {
+    const INDEX_TYPE: IndexType = IndexType::U32;
+
+    fn to_usize(self) -> usize {
+        self as usize
+    }
+}
+
+// Reinterprets a slice of Pod values as a slice of another Pod type.
+//
+// Panics when the data is not aligned for B or its size is not a multiple of B.
+pub fn cast_slice<A: Pod, B: Pod>(data: &[A]) -> &[B] {
+    let bytes = size_of_val(data);
+    assert!(size_of::<B>() != 0, "cast_slice: zero sized target type");
+    assert!(bytes % size_of::<B>() == 0, "cast_slice: size mismatch");
+    assert!(data.as_ptr() as usize % align_of::<B>() == 0, "cast_slice: misaligned data");
+
+    // SAFETY: A and B are Pod, so any bytes of A are a valid B. Size and
+    // alignment are checked above, and the lifetime is carried over.
+    unsafe { std::slice::from_raw_parts(data.as_ptr() as *const B, bytes / size_of::<B>()) }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Vertex)]
+#[repr(C)]
+pub struct UVMappedVertex {
+    pub position: Vector3,
+    #[vertex(name = "textureCoord")]
+    pub texture_coordination: Vector2,
+}
+
+impl From<(Vector3, Vector2)> for UVMappedVertex {
+    fn from(value: (Vector3, Vector2)) -> Self {
+        let (position, texture_coordination) = value;
+        UVMappedVertex { position, texture_coordination }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Vertex)]
+#[repr(C)]
+pub struct ColoredVertex {
+    pub position: Vector3,
+    pub color: Vector4,
+}
+
+impl From<(Vector3, Vector4)> for ColoredVertex {
+    fn from(value: (Vector3, Vector4)) -> Self {
+        let (position, color) = value;
+        ColoredVertex { position, color }
+    }
+}
@@ -1,9 +1,26 @@
 mod matrix;
+mod matrix3;
 mod vector;
 mod rectangle;
+mod quaternion;
+mod bounds;
+mod frustum;
+mod ray;
+mod color;
+pub mod curve;
+mod approx;
 pub use vector::*;
 pub use matrix::*;
+pub use matrix3::*;
 pub use rectangle::*;
+pub use quaternion::*;
+pub use bounds::*;
+pub use frustum::*;
+pub use ray::*;
+pub use color::*;
+// Only used by tests within this crate so far.
+#[allow(unused_imports)]
+pub use approx::*;
 
 #[cfg(test)]
 mod tests;
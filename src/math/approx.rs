@@ -0,0 +1,86 @@
+use super::{Matrix3, Matrix4, Quaternion, Vector2, Vector3, Vector4};
+
+// Floating point comparison with a tolerance.
+//
+// Two values are equal when every component pair (a, b) satisfies
+// `|a - b| <= max(abs_eps, rel_eps * max(|a|, |b|))`.
+// The absolute epsilon handles values near zero, the relative one large values.
+pub trait ApproxEq {
+    const DEFAULT_ABS_EPSILON: f32 = 1e-5;
+    const DEFAULT_REL_EPSILON: f32 = 1e-5;
+
+    fn approx_eq_eps(&self, other: &Self, abs_eps: f32, rel_eps: f32) -> bool;
+
+    fn approx_eq(&self, other: &Self) -> bool {
+        self.approx_eq_eps(other, Self::DEFAULT_ABS_EPSILON, Self::DEFAULT_REL_EPSILON)
+    }
+}
+
+impl ApproxEq for f32 {
+    fn approx_eq_eps(&self, other: &Self, abs_eps: f32, rel_eps: f32) -> bool {
+        if self == other {
+            return true;
+        }
+        let diff = (self - other).abs();
+        diff <= abs_eps.max(rel_eps * self.abs().max(other.abs()))
+    }
+}
+
+impl ApproxEq for [f32] {
+    fn approx_eq_eps(&self, other: &Self, abs_eps: f32, rel_eps: f32) -> bool {
+        self.len() == other.len() &&
+            self.iter().zip(other).all(|(a, b)| a.approx_eq_eps(b, abs_eps, rel_eps))
+    }
+}
+
+impl ApproxEq for Vector2 {
+    fn approx_eq_eps(&self, other: &Self, abs_eps: f32, rel_eps: f32) -> bool {
+        [self.x, self.y][..].approx_eq_eps(&[other.x, other.y], abs_eps, rel_eps)
+    }
+}
+
+impl ApproxEq for Vector3 {
+    fn approx_eq_eps(&self, other: &Self, abs_eps: f32, rel_eps: f32) -> bool {
+        [self.x, self.y, self.z][..].approx_eq_eps(&[other.x, other.y, other.z], abs_eps, rel_eps)
+    }
+}
+
+impl ApproxEq for Vector4 {
+    fn approx_eq_eps(&self, other: &Self, abs_eps: f32, rel_eps: f32) -> bool {
+        [self.x, self.y, self.z, self.w][..].approx_eq_eps(&[other.x, other.y, other.z, other.w], abs_eps, rel_eps)
+    }
+}
+
+impl ApproxEq for Matrix3 {
+    fn approx_eq_eps(&self, other: &Self, abs_eps: f32, rel_eps: f32) -> bool {
+        self.as_ref().approx_eq_eps(other.as_ref(), abs_eps, rel_eps)
+    }
+}
+
+impl ApproxEq for Matrix4 {
+    fn approx_eq_eps(&self, other: &Self, abs_eps: f32, rel_eps: f32) -> bool {
+        self.as_ref().approx_eq_eps(other.as_ref(), abs_eps, rel_eps)
+    }
+}
+
+// q and -q are the same rotation, so either sign is accepted.
+impl ApproxEq for Quaternion {
+    fn approx_eq_eps(&self, other: &Self, abs_eps: f32, rel_eps: f32) -> bool {
+        self.0.approx_eq_eps(&other.0, abs_eps, rel_eps) ||
+            self.0.approx_eq_eps(&-other.0, abs_eps, rel_eps)
+    }
+}
+
+// Like assert_eq!, but compares with ApproxEq::approx_eq.
+#[macro_export]
+macro_rules! assert_approx_eq {
+    ($left:expr, $right:expr $(,)?) => {{
+        let (left, right) = (&$left, &$right);
+        assert!(
+            $crate::math::ApproxEq::approx_eq(left, right),
+            "assertion failed: `left ≈ right`\n  left: `{:?}`\n right: `{:?}`",
+            left,
+            right
+        )
+    }};
+}
@@ -0,0 +1,105 @@
+use crate::vec3;
+
+use super::{Matrix4, Vector3};
+
+// Axis-aligned bounding box.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Aabb {
+    pub min: Vector3,
+    pub max: Vector3,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BoundingSphere {
+    pub center: Vector3,
+    pub radius: f32,
+}
+
+#[allow(dead_code)]
+impl Aabb {
+    pub fn new(min: Vector3, max: Vector3) -> Aabb {
+        Aabb { min, max }
+    }
+
+    // Returns None when there are no points.
+    pub fn from_points<I: IntoIterator<Item = Vector3>>(points: I) -> Option<Aabb> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(points.fold(Aabb::new(first, first), |b, p| b.expand(p)))
+    }
+
+    pub fn center(&self) -> Vector3 {
+        (self.min + self.max) * 0.5
+    }
+
+    // Half of the size along each axis.
+    pub fn extents(&self) -> Vector3 {
+        (self.max - self.min) * 0.5
+    }
+
+    pub fn size(&self) -> Vector3 {
+        self.max - self.min
+    }
+
+    pub fn expand(&self, point: Vector3) -> Aabb {
+        Aabb::new(self.min.min(point), self.max.max(point))
+    }
+
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Aabb::new(self.min.min(other.min), self.max.max(other.max))
+    }
+
+    pub fn contains_point(&self, point: Vector3) -> bool {
+        point.x >= self.min.x && point.x <= self.max.x &&
+        point.y >= self.min.y && point.y <= self.max.y &&
+        point.z >= self.min.z && point.z <= self.max.z
+    }
+
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        self.min.x <= other.max.x && self.max.x >= other.min.x &&
+        self.min.y <= other.max.y && self.max.y >= other.min.y &&
+        self.min.z <= other.max.z && self.max.z >= other.min.z
+    }
+
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        let closest = sphere.center.clamp(self.min, self.max);
+        let d = closest - sphere.center;
+        d.dot(d) <= sphere.radius * sphere.radius
+    }
+
+    // Box enclosing this box after an affine transform.
+    pub fn transform(&self, m: &Matrix4) -> Aabb {
+        let center = *m * self.center();
+        let vec3!(ex, ey, ez) = self.extents();
+        let extents = vec3!(
+            m.m11.abs() * ex + m.m12.abs() * ey + m.m13.abs() * ez,
+            m.m21.abs() * ex + m.m22.abs() * ey + m.m23.abs() * ez,
+            m.m31.abs() * ex + m.m32.abs() * ey + m.m33.abs() * ez
+        );
+        Aabb::new(center - extents, center + extents)
+    }
+}
+
+#[allow(dead_code)]
+impl BoundingSphere {
+    pub fn new(center: Vector3, radius: f32) -> BoundingSphere {
+        BoundingSphere { center, radius }
+    }
+
+    pub fn contains_point(&self, point: Vector3) -> bool {
+        let d = point - self.center;
+        d.dot(d) <= self.radius * self.radius
+    }
+
+    pub fn intersects(&self, other: &BoundingSphere) -> bool {
+        let d = other.center - self.center;
+        let r = self.radius + other.radius;
+        d.dot(d) <= r * r
+    }
+}
+
+impl From<Aabb> for BoundingSphere {
+    fn from(value: Aabb) -> Self {
+        BoundingSphere::new(value.center(), value.extents().norm())
+    }
+}
@@ -0,0 +1,352 @@
+use std::str::FromStr;
+
+use crate::{error::Error, vec4};
+
+use super::Vector4;
+
+// RGBA color with components in [0, 1].
+//
+// Whether the RGB components are sRGB encoded or linear is up to the caller;
+// constructors from hex strings, CSS names and 8-bit values produce sRGB.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[repr(C)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+#[allow(dead_code)]
+impl Color {
+    pub const TRANSPARENT: Color = Color::new(0.0, 0.0, 0.0, 0.0);
+    pub const BLACK: Color = Color::new(0.0, 0.0, 0.0, 1.0);
+    pub const WHITE: Color = Color::new(1.0, 1.0, 1.0, 1.0);
+
+    pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Color {
+        Color { r, g, b, a }
+    }
+
+    pub fn from_rgba8(r: u8, g: u8, b: u8, a: u8) -> Color {
+        Color::new(
+            r as f32 / 255.0,
+            g as f32 / 255.0,
+            b as f32 / 255.0,
+            a as f32 / 255.0,
+        )
+    }
+
+    pub fn to_rgba8(self) -> (u8, u8, u8, u8) {
+        let to_u8 = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+        (to_u8(self.r), to_u8(self.g), to_u8(self.b), to_u8(self.a))
+    }
+
+    // Accepts "rgb", "rgba", "rrggbb" and "rrggbbaa", with or without a leading '#'.
+    pub fn from_hex(hex: &str) -> Result<Color, Error> {
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+        let invalid = || Error::from(format!("invalid hex color: {}", hex));
+
+        let values = digits
+            .chars()
+            .map(|c| c.to_digit(16).map(|d| d as u8))
+            .collect::<Option<Vec<u8>>>()
+            .ok_or_else(invalid)?;
+
+        match values.as_slice() {
+            [r, g, b] => Ok(Color::from_rgba8(r * 17, g * 17, b * 17, 255)),
+            [r, g, b, a] => Ok(Color::from_rgba8(r * 17, g * 17, b * 17, a * 17)),
+            [r1, r2, g1, g2, b1, b2] => Ok(Color::from_rgba8(r1 * 16 + r2, g1 * 16 + g2, b1 * 16 + b2, 255)),
+            [r1, r2, g1, g2, b1, b2, a1, a2] => Ok(Color::from_rgba8(r1 * 16 + r2, g1 * 16 + g2, b1 * 16 + b2, a1 * 16 + a2)),
+            _ => Err(invalid()),
+        }
+    }
+
+    // CSS named colors, case insensitive. "transparent" is included.
+    pub fn from_css_name(name: &str) -> Option<Color> {
+        let name = name.to_ascii_lowercase();
+        if name == "transparent" {
+            return Some(Color::TRANSPARENT);
+        }
+        let i = CSS_COLORS.binary_search_by(|(n, _)| (*n).cmp(name.as_str())).ok()?;
+        let rgb = CSS_COLORS[i].1;
+        Some(Color::from_rgba8((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8, 255))
+    }
+
+    pub fn srgb_to_linear(self) -> Color {
+        let f = |c: f32| {
+            if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        Color::new(f(self.r), f(self.g), f(self.b), self.a)
+    }
+
+    pub fn linear_to_srgb(self) -> Color {
+        let f = |c: f32| {
+            if c <= 0.0031308 {
+                c * 12.92
+            } else {
+                1.055 * c.powf(1.0 / 2.4) - 0.055
+            }
+        };
+        Color::new(f(self.r), f(self.g), f(self.b), self.a)
+    }
+
+    // Hue in degrees [0, 360), saturation and value in [0, 1].
+    pub fn from_hsv(h: f32, s: f32, v: f32, a: f32) -> Color {
+        let c = v * s;
+        let (r, g, b) = hue_to_rgb(h, c);
+        let m = v - c;
+        Color::new(r + m, g + m, b + m, a)
+    }
+
+    // Returns (hue, saturation, value). Alpha is not included.
+    pub fn to_hsv(self) -> (f32, f32, f32) {
+        let (max, min, h) = self.hue();
+        let s = if max == 0.0 { 0.0 } else { (max - min) / max };
+        (h, s, max)
+    }
+
+    // Hue in degrees [0, 360), saturation and lightness in [0, 1].
+    pub fn from_hsl(h: f32, s: f32, l: f32, a: f32) -> Color {
+        let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        let (r, g, b) = hue_to_rgb(h, c);
+        let m = l - c / 2.0;
+        Color::new(r + m, g + m, b + m, a)
+    }
+
+    // Returns (hue, saturation, lightness). Alpha is not included.
+    pub fn to_hsl(self) -> (f32, f32, f32) {
+        let (max, min, h) = self.hue();
+        let l = (max + min) / 2.0;
+        let d = max - min;
+        let s = if d == 0.0 { 0.0 } else { d / (1.0 - (2.0 * l - 1.0).abs()) };
+        (h, s, l)
+    }
+
+    pub fn premultiply(self) -> Color {
+        Color::new(self.r * self.a, self.g * self.a, self.b * self.a, self.a)
+    }
+
+    // Fully transparent colors become TRANSPARENT.
+    pub fn unpremultiply(self) -> Color {
+        if self.a == 0.0 {
+            return Color::TRANSPARENT;
+        }
+        Color::new(self.r / self.a, self.g / self.a, self.b / self.a, self.a)
+    }
+
+    // (max, min, hue in degrees) shared by HSV and HSL.
+    fn hue(self) -> (f32, f32, f32) {
+        let Color { r, g, b, .. } = self;
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let d = max - min;
+
+        let h = if d == 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / d).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / d + 2.0)
+        } else {
+            60.0 * ((r - g) / d + 4.0)
+        };
+        (max, min, h)
+    }
+}
+
+// RGB of a color with the given hue and chroma, before adding the lightness offset.
+fn hue_to_rgb(h: f32, c: f32) -> (f32, f32, f32) {
+    let h = h.rem_euclid(360.0) / 60.0;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    match h as i32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    }
+}
+
+// Accepts a hex color starting with '#' or a CSS color name.
+impl FromStr for Color {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.starts_with('#') {
+            return Color::from_hex(s);
+        }
+        Color::from_css_name(s).ok_or_else(|| format!("unknown color: {}", s).into())
+    }
+}
+
+impl From<Color> for Vector4 {
+    fn from(value: Color) -> Self {
+        let Color { r, g, b, a } = value;
+        vec4!(r, g, b, a)
+    }
+}
+
+impl From<Vector4> for Color {
+    fn from(value: Vector4) -> Self {
+        let vec4!(x, y, z, w) = value;
+        Color::new(x, y, z, w)
+    }
+}
+
+// Sorted by name for binary search.
+const CSS_COLORS: [(&str, u32); 148] = [
+    ("aliceblue", 0xf0f8ff),
+    ("antiquewhite", 0xfaebd7),
+    ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff),
+    ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0),
+    ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50),
+    ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222),
+    ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff),
+    ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xadff2f),
+    ("grey", 0x808080),
+    ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4),
+    ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd),
+    ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff),
+    ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90),
+    ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0),
+    ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6),
+    ("magenta", 0xff00ff),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd),
+    ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db),
+    ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a),
+    ("mediumturquoise", 0x48d1cc),
+    ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xf5fffa),
+    ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead),
+    ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500),
+    ("orangered", 0xff4500),
+    ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa),
+    ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f),
+    ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f),
+    ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072),
+    ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57),
+    ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0),
+    ("skyblue", 0x87ceeb),
+    ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4),
+    ("tan", 0xd2b48c),
+    ("teal", 0x008080),
+    ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0),
+    ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3),
+    ("white", 0xffffff),
+    ("whitesmoke", 0xf5f5f5),
+    ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
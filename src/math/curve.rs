@@ -0,0 +1,389 @@
+use std::f32::consts::PI;
+
+use crate::error::Error;
+use super::{Quaternion, Vector2, Vector3, Vector4};
+
+// Values that curves can be built over.
+pub trait Interpolate: Copy {
+    fn lerp(self, rhs: Self, t: f32) -> Self;
+    // self + tangent * t. Used to turn Hermite tangents into control points.
+    fn add_scaled(self, tangent: Self, t: f32) -> Self;
+    // Used for arc-length parameterization.
+    fn distance(self, rhs: Self) -> f32;
+}
+
+impl Interpolate for f32 {
+    fn lerp(self, rhs: Self, t: f32) -> Self {
+        self + (rhs - self) * t
+    }
+
+    fn add_scaled(self, tangent: Self, t: f32) -> Self {
+        self + tangent * t
+    }
+
+    fn distance(self, rhs: Self) -> f32 {
+        (rhs - self).abs()
+    }
+}
+
+impl Interpolate for Vector2 {
+    fn lerp(self, rhs: Self, t: f32) -> Self {
+        Vector2::lerp(self, rhs, t)
+    }
+
+    fn add_scaled(self, tangent: Self, t: f32) -> Self {
+        self + tangent * t
+    }
+
+    fn distance(self, rhs: Self) -> f32 {
+        (rhs - self).norm()
+    }
+}
+
+impl Interpolate for Vector3 {
+    fn lerp(self, rhs: Self, t: f32) -> Self {
+        Vector3::lerp(self, rhs, t)
+    }
+
+    fn add_scaled(self, tangent: Self, t: f32) -> Self {
+        self + tangent * t
+    }
+
+    fn distance(self, rhs: Self) -> f32 {
+        (rhs - self).norm()
+    }
+}
+
+impl Interpolate for Vector4 {
+    fn lerp(self, rhs: Self, t: f32) -> Self {
+        Vector4::lerp(self, rhs, t)
+    }
+
+    fn add_scaled(self, tangent: Self, t: f32) -> Self {
+        self + tangent * t
+    }
+
+    fn distance(self, rhs: Self) -> f32 {
+        (rhs - self).norm()
+    }
+}
+
+// Quaternions interpolate with slerp. Tangents are treated as 4D derivatives
+// and the result is renormalized. Distance is the rotation angle between them.
+impl Interpolate for Quaternion {
+    fn lerp(self, rhs: Self, t: f32) -> Self {
+        self.slerp(rhs, t)
+    }
+
+    fn add_scaled(self, tangent: Self, t: f32) -> Self {
+        Quaternion(self.0 + tangent.0 * t).normalize()
+    }
+
+    fn distance(self, rhs: Self) -> f32 {
+        2.0 * self.dot(rhs).abs().min(1.0).acos()
+    }
+}
+
+#[allow(dead_code)]
+pub fn lerp<T: Interpolate>(a: T, b: T, t: f32) -> T {
+    a.lerp(b, t)
+}
+
+// Hermite interpolation of x between edge0 and edge1, clamped to [0, 1].
+#[allow(dead_code)]
+pub fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+// Like smoothstep, but also with zero second derivative at the edges.
+#[allow(dead_code)]
+pub fn smootherstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+// Robert Penner's easing functions. All map 0 to 0 and 1 to 1.
+#[allow(dead_code)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Easing {
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    QuartIn,
+    QuartOut,
+    QuartInOut,
+    QuintIn,
+    QuintOut,
+    QuintInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    ExpoIn,
+    ExpoOut,
+    ExpoInOut,
+    CircIn,
+    CircOut,
+    CircInOut,
+    BackIn,
+    BackOut,
+    BackInOut,
+    ElasticIn,
+    ElasticOut,
+    ElasticInOut,
+    BounceIn,
+    BounceOut,
+    BounceInOut,
+}
+
+impl Easing {
+    // t is clamped to [0, 1].
+    pub fn ease(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::QuadIn => t.powi(2),
+            Easing::QuadOut => ease_out(t, |t| t.powi(2)),
+            Easing::QuadInOut => ease_in_out(t, |t| t.powi(2)),
+            Easing::CubicIn => t.powi(3),
+            Easing::CubicOut => ease_out(t, |t| t.powi(3)),
+            Easing::CubicInOut => ease_in_out(t, |t| t.powi(3)),
+            Easing::QuartIn => t.powi(4),
+            Easing::QuartOut => ease_out(t, |t| t.powi(4)),
+            Easing::QuartInOut => ease_in_out(t, |t| t.powi(4)),
+            Easing::QuintIn => t.powi(5),
+            Easing::QuintOut => ease_out(t, |t| t.powi(5)),
+            Easing::QuintInOut => ease_in_out(t, |t| t.powi(5)),
+            Easing::SineIn => sine_in(t),
+            Easing::SineOut => ease_out(t, sine_in),
+            Easing::SineInOut => ease_in_out(t, sine_in),
+            Easing::ExpoIn => expo_in(t),
+            Easing::ExpoOut => ease_out(t, expo_in),
+            Easing::ExpoInOut => ease_in_out(t, expo_in),
+            Easing::CircIn => circ_in(t),
+            Easing::CircOut => ease_out(t, circ_in),
+            Easing::CircInOut => ease_in_out(t, circ_in),
+            Easing::BackIn => back_in(t),
+            Easing::BackOut => ease_out(t, back_in),
+            Easing::BackInOut => ease_in_out(t, back_in),
+            Easing::ElasticIn => elastic_in(t),
+            Easing::ElasticOut => ease_out(t, elastic_in),
+            Easing::ElasticInOut => ease_in_out(t, elastic_in),
+            Easing::BounceIn => ease_out(t, bounce_out),
+            Easing::BounceOut => bounce_out(t),
+            Easing::BounceInOut => ease_in_out(t, |t| ease_out(t, bounce_out)),
+        }
+    }
+}
+
+// Out variant of an in easing: mirrored in both axes.
+fn ease_out(t: f32, f: impl Fn(f32) -> f32) -> f32 {
+    1.0 - f(1.0 - t)
+}
+
+// In for the first half, out for the second half.
+fn ease_in_out(t: f32, f: impl Fn(f32) -> f32) -> f32 {
+    if t < 0.5 {
+        f(t * 2.0) / 2.0
+    } else {
+        1.0 - f((1.0 - t) * 2.0) / 2.0
+    }
+}
+
+fn sine_in(t: f32) -> f32 {
+    1.0 - (t * PI / 2.0).cos()
+}
+
+fn expo_in(t: f32) -> f32 {
+    if t == 0.0 {
+        0.0
+    } else {
+        2.0f32.powf(10.0 * t - 10.0)
+    }
+}
+
+fn circ_in(t: f32) -> f32 {
+    1.0 - (1.0 - t * t).sqrt()
+}
+
+fn back_in(t: f32) -> f32 {
+    const C1: f32 = 1.70158;
+    const C3: f32 = C1 + 1.0;
+    C3 * t * t * t - C1 * t * t
+}
+
+fn elastic_in(t: f32) -> f32 {
+    if t == 0.0 || t == 1.0 {
+        return t;
+    }
+    const C4: f32 = 2.0 * PI / 3.0;
+    -(2.0f32.powf(10.0 * t - 10.0)) * ((t * 10.0 - 10.75) * C4).sin()
+}
+
+fn bounce_out(t: f32) -> f32 {
+    const N1: f32 = 7.5625;
+    const D1: f32 = 2.75;
+    if t < 1.0 / D1 {
+        N1 * t * t
+    } else if t < 2.0 / D1 {
+        let t = t - 1.5 / D1;
+        N1 * t * t + 0.75
+    } else if t < 2.5 / D1 {
+        let t = t - 2.25 / D1;
+        N1 * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D1;
+        N1 * t * t + 0.984375
+    }
+}
+
+// Curves that can be evaluated for t in [0, 1].
+pub trait Curve<T: Interpolate> {
+    fn evaluate(&self, t: f32) -> T;
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CubicBezier<T: Interpolate> {
+    pub p0: T,
+    pub p1: T,
+    pub p2: T,
+    pub p3: T,
+}
+
+// de Casteljau's algorithm, so that it only needs lerp.
+impl<T: Interpolate> Curve<T> for CubicBezier<T> {
+    fn evaluate(&self, t: f32) -> T {
+        let a = self.p0.lerp(self.p1, t);
+        let b = self.p1.lerp(self.p2, t);
+        let c = self.p2.lerp(self.p3, t);
+        let d = a.lerp(b, t);
+        let e = b.lerp(c, t);
+        d.lerp(e, t)
+    }
+}
+
+// Cubic Hermite segment from p0 to p1 with tangents m0 and m1.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Hermite<T: Interpolate> {
+    pub p0: T,
+    pub m0: T,
+    pub p1: T,
+    pub m1: T,
+}
+
+impl<T: Interpolate> From<Hermite<T>> for CubicBezier<T> {
+    fn from(value: Hermite<T>) -> Self {
+        CubicBezier {
+            p0: value.p0,
+            p1: value.p0.add_scaled(value.m0, 1.0 / 3.0),
+            p2: value.p1.add_scaled(value.m1, -1.0 / 3.0),
+            p3: value.p1,
+        }
+    }
+}
+
+impl<T: Interpolate> Curve<T> for Hermite<T> {
+    fn evaluate(&self, t: f32) -> T {
+        CubicBezier::from(*self).evaluate(t)
+    }
+}
+
+// Uniform Catmull-Rom spline passing through every point.
+// The first and last points only shape the ends and are not reached,
+// so at least four points are needed. `new` checks that.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CatmullRom<T: Interpolate> {
+    points: Vec<T>,
+}
+
+#[allow(dead_code)]
+impl<T: Interpolate> CatmullRom<T> {
+    pub fn new(points: Vec<T>) -> Result<CatmullRom<T>, Error> {
+        if points.len() < 4 {
+            return Err(format!("CatmullRom needs at least four points, got {}", points.len()).into());
+        }
+        Ok(CatmullRom { points })
+    }
+
+    pub fn points(&self) -> &[T] {
+        &self.points
+    }
+
+    pub fn segment_count(&self) -> usize {
+        self.points.len() - 3
+    }
+
+    // Evaluates segment i (between points i + 1 and i + 2) at local t.
+    // Barry and Goldman's pyramidal formulation, so that it only needs lerp.
+    pub fn evaluate_segment(&self, i: usize, t: f32) -> T {
+        let (p0, p1, p2, p3) = (self.points[i], self.points[i + 1], self.points[i + 2], self.points[i + 3]);
+        let a1 = p0.lerp(p1, t + 1.0);
+        let a2 = p1.lerp(p2, t);
+        let a3 = p2.lerp(p3, t - 1.0);
+        let b1 = a1.lerp(a2, (t + 1.0) / 2.0);
+        let b2 = a2.lerp(a3, t / 2.0);
+        b1.lerp(b2, t)
+    }
+}
+
+// t in [0, 1] covers the whole spline, each segment taking an equal share.
+impl<T: Interpolate> Curve<T> for CatmullRom<T> {
+    fn evaluate(&self, t: f32) -> T {
+        let n = self.segment_count();
+        let x = t.clamp(0.0, 1.0) * n as f32;
+        let i = (x as usize).min(n - 1);
+        self.evaluate_segment(i, x - i as f32)
+    }
+}
+
+// Maps distance along a curve back to its parameter, so that the curve can be
+// traversed at constant speed. Built by sampling the curve into line segments.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArcLength {
+    // Accumulated length at each of the evenly spaced samples.
+    lengths: Vec<f32>,
+}
+
+#[allow(dead_code)]
+impl ArcLength {
+    pub fn new<T: Interpolate, C: Curve<T>>(curve: &C, samples: usize) -> ArcLength {
+        let samples = samples.max(1);
+        let mut lengths = Vec::with_capacity(samples + 1);
+        let mut previous = curve.evaluate(0.0);
+        let mut total = 0.0;
+        lengths.push(total);
+        for i in 1..=samples {
+            let p = curve.evaluate(i as f32 / samples as f32);
+            total += previous.distance(p);
+            lengths.push(total);
+            previous = p;
+        }
+        ArcLength { lengths }
+    }
+
+    pub fn total_length(&self) -> f32 {
+        *self.lengths.last().unwrap()
+    }
+
+    // Curve parameter at the given distance from the start.
+    pub fn parameter(&self, distance: f32) -> f32 {
+        let samples = (self.lengths.len() - 1) as f32;
+        let distance = distance.clamp(0.0, self.total_length());
+        let i = self.lengths.partition_point(|&l| l < distance);
+        if i == 0 {
+            return 0.0;
+        }
+        let (l0, l1) = (self.lengths[i - 1], self.lengths[i]);
+        let f = if l1 > l0 { (distance - l0) / (l1 - l0) } else { 0.0 };
+        ((i - 1) as f32 + f) / samples
+    }
+
+    // Curve parameter at the given fraction [0, 1] of the total length.
+    pub fn parameter_at_fraction(&self, fraction: f32) -> f32 {
+        self.parameter(fraction * self.total_length())
+    }
+}
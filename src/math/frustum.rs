@@ -0,0 +1,98 @@
+use crate::{mat4, vec3};
+
+use super::{Aabb, BoundingSphere, Matrix4, Vector3};
+
+// Plane of points p satisfying `normal.dot(p) + d == 0`.
+// The normal points to the front (positive) side.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Plane {
+    pub normal: Vector3,
+    pub d: f32,
+}
+
+#[allow(dead_code)]
+impl Plane {
+    pub fn new(normal: Vector3, d: f32) -> Plane {
+        Plane { normal, d }
+    }
+
+    pub fn from_point_normal(point: Vector3, normal: Vector3) -> Plane {
+        let normal = normal.normalize();
+        Plane::new(normal, -normal.dot(point))
+    }
+
+    // Scales the plane equation so that the normal has unit length.
+    // Degenerate planes (zero normal) are returned as is.
+    pub fn normalize(self) -> Plane {
+        let n = self.normal.norm();
+        if n == 0.0 {
+            return self;
+        }
+        Plane::new(self.normal / n, self.d / n)
+    }
+
+    // Signed distance; positive on the front side. Assumes a normalized plane.
+    pub fn distance(&self, point: Vector3) -> f32 {
+        self.normal.dot(point) + self.d
+    }
+}
+
+// View frustum as six inward-facing planes:
+// left, right, bottom, top, near and far.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Frustum {
+    pub planes: [Plane; 6],
+}
+
+#[allow(dead_code)]
+impl Frustum {
+    pub fn contains_point(&self, point: Vector3) -> bool {
+        self.planes.iter().all(|p| p.distance(point) >= 0.0)
+    }
+
+    // Conservative: boxes near the frustum corners may be reported
+    // as intersecting even though they are outside.
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        self.planes.iter().all(|p| {
+            // The corner furthest along the plane normal.
+            let corner = vec3!(
+                if p.normal.x >= 0.0 { aabb.max.x } else { aabb.min.x },
+                if p.normal.y >= 0.0 { aabb.max.y } else { aabb.min.y },
+                if p.normal.z >= 0.0 { aabb.max.z } else { aabb.min.z }
+            );
+            p.distance(corner) >= 0.0
+        })
+    }
+
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        self.planes.iter().all(|p| p.distance(sphere.center) >= -sphere.radius)
+    }
+}
+
+// Extracts the planes from a projection-view (or projection-view-world) matrix.
+// The planes are in the space the matrix transforms from.
+// Works with every projection in Matrix4, including reverse-Z and infinite far,
+// since they all clip z to [-w, w].
+impl From<Matrix4> for Frustum {
+    fn from(value: Matrix4) -> Self {
+        let mat4!(
+            a11, a12, a13, a14,
+            a21, a22, a23, a24,
+            a31, a32, a33, a34,
+            a41, a42, a43, a44
+        ) = value;
+
+        let plane = |x: f32, y: f32, z: f32, w: f32| Plane::new(vec3!(x, y, z), w).normalize();
+
+        Frustum {
+            planes: [
+                plane(a41 + a11, a42 + a12, a43 + a13, a44 + a14),
+                plane(a41 - a11, a42 - a12, a43 - a13, a44 - a14),
+                plane(a41 + a21, a42 + a22, a43 + a23, a44 + a24),
+                plane(a41 - a21, a42 - a22, a43 - a23, a44 - a24),
+                plane(a41 + a31, a42 + a32, a43 + a33, a44 + a34),
+                plane(a41 - a31, a42 - a32, a43 - a33, a44 - a34),
+            ],
+        }
+    }
+}
@@ -1,9 +1,9 @@
 use std::{mem::size_of, ops::Mul};
 
-use crate::vec3;
+use crate::{vec3, vec4};
 use crate::mat4;
 
-use super::Vector3;
+use super::{Quaternion, Ray, Size, Vector2, Vector3, Vector4};
 
 #[derive(Debug, Copy, Clone)]
 #[repr(C)]
@@ -91,6 +91,25 @@ impl Mul<Vector3> for Matrix4 {
     }
 }
 
+impl Mul<Vector4> for Matrix4 {
+    type Output = Vector4;
+
+    fn mul(self, rhs: Vector4) -> Self::Output {
+        let mat4!(
+            a11, a12, a13, a14,
+            a21, a22, a23, a24,
+            a31, a32, a33, a34,
+            a41, a42, a43, a44
+        ) = self;
+        let vec4!(x, y, z, w) = rhs;
+        vec4!(
+            a11*x + a12*y + a13*z + a14*w,
+            a21*x + a22*y + a23*z + a24*w,
+            a31*x + a32*y + a33*z + a34*w,
+            a41*x + a42*y + a43*z + a44*w)
+    }
+}
+
 impl AsRef<[f32]> for Matrix4 {
     fn as_ref(&self) -> &[f32] {
         unsafe {
@@ -203,6 +222,7 @@ impl Matrix4 {
         }
     }
 
+    #[allow(dead_code)]
     pub fn perspective_fov(fov_y: f32, aspect_ratio: f32, near: f32, far: f32) -> Matrix4 {
         let sy = 1.0f32/(fov_y/2.0).tan();
         let sx = sy / aspect_ratio;
@@ -214,6 +234,251 @@ impl Matrix4 {
             0.0, 0.0, -1.0, 0.0
         )
     } 
+
+    // Same as perspective_fov, but maps the near plane to +1 and the far plane to -1
+    // in NDC. Pair with DepthConvention::ReverseZ.
+    #[allow(dead_code)]
+    pub fn perspective_reverse_z(fov_y: f32, aspect_ratio: f32, near: f32, far: f32) -> Matrix4 {
+        let sy = 1.0f32/(fov_y/2.0).tan();
+        let sx = sy / aspect_ratio;
+
+        mat4!(
+            sx, 0.0, 0.0, 0.0,
+            0.0, sy, 0.0, 0.0,
+            0.0, 0.0, (far + near)/(far - near), 2.0 * near * far/(far - near),
+            0.0, 0.0, -1.0, 0.0
+        )
+    }
+
+    // perspective_fov with the far plane at infinity.
+    #[allow(dead_code)]
+    pub fn perspective_infinite(fov_y: f32, aspect_ratio: f32, near: f32) -> Matrix4 {
+        let sy = 1.0f32/(fov_y/2.0).tan();
+        let sx = sy / aspect_ratio;
+
+        mat4!(
+            sx, 0.0, 0.0, 0.0,
+            0.0, sy, 0.0, 0.0,
+            0.0, 0.0, -1.0, -2.0 * near,
+            0.0, 0.0, -1.0, 0.0
+        )
+    }
+
+    // perspective_reverse_z with the far plane at infinity.
+    #[allow(dead_code)]
+    pub fn perspective_infinite_reverse_z(fov_y: f32, aspect_ratio: f32, near: f32) -> Matrix4 {
+        let sy = 1.0f32/(fov_y/2.0).tan();
+        let sx = sy / aspect_ratio;
+
+        mat4!(
+            sx, 0.0, 0.0, 0.0,
+            0.0, sy, 0.0, 0.0,
+            0.0, 0.0, 1.0, 2.0 * near,
+            0.0, 0.0, -1.0, 0.0
+        )
+    }
+
+    #[allow(dead_code)]
+    pub fn orthographic(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Matrix4 {
+        mat4!(
+            2.0/(right - left), 0.0, 0.0, -(right + left)/(right - left),
+            0.0, 2.0/(top - bottom), 0.0, -(top + bottom)/(top - bottom),
+            0.0, 0.0, -2.0/(far - near), -(far + near)/(far - near),
+            0.0, 0.0, 0.0, 1.0
+        )
+    }
+
+    #[allow(dead_code)]
+    pub fn determinant(&self) -> f32 {
+        let mat4!(
+            a11, a12, a13, a14,
+            a21, a22, a23, a24,
+            a31, a32, a33, a34,
+            a41, a42, a43, a44
+        ) = *self;
+
+        let s0 = a11 * a22 - a21 * a12;
+        let s1 = a11 * a23 - a21 * a13;
+        let s2 = a11 * a24 - a21 * a14;
+        let s3 = a12 * a23 - a22 * a13;
+        let s4 = a12 * a24 - a22 * a14;
+        let s5 = a13 * a24 - a23 * a14;
+
+        let c5 = a33 * a44 - a43 * a34;
+        let c4 = a32 * a44 - a42 * a34;
+        let c3 = a32 * a43 - a42 * a33;
+        let c2 = a31 * a44 - a41 * a34;
+        let c1 = a31 * a43 - a41 * a33;
+        let c0 = a31 * a42 - a41 * a32;
+
+        s0 * c5 - s1 * c4 + s2 * c3 + s3 * c2 - s4 * c1 + s5 * c0
+    }
+
+    // Returns None when the matrix is singular.
+    #[allow(dead_code)]
+    #[rustfmt::skip]
+    pub fn inverse(&self) -> Option<Matrix4> {
+        let mat4!(
+            a11, a12, a13, a14,
+            a21, a22, a23, a24,
+            a31, a32, a33, a34,
+            a41, a42, a43, a44
+        ) = *self;
+
+        // 2x2 minors of the upper two rows (s) and the lower two rows (c).
+        let s0 = a11 * a22 - a21 * a12;
+        let s1 = a11 * a23 - a21 * a13;
+        let s2 = a11 * a24 - a21 * a14;
+        let s3 = a12 * a23 - a22 * a13;
+        let s4 = a12 * a24 - a22 * a14;
+        let s5 = a13 * a24 - a23 * a14;
+
+        let c5 = a33 * a44 - a43 * a34;
+        let c4 = a32 * a44 - a42 * a34;
+        let c3 = a32 * a43 - a42 * a33;
+        let c2 = a31 * a44 - a41 * a34;
+        let c1 = a31 * a43 - a41 * a33;
+        let c0 = a31 * a42 - a41 * a32;
+
+        let det = s0 * c5 - s1 * c4 + s2 * c3 + s3 * c2 - s4 * c1 + s5 * c0;
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        let d = 1.0 / det;
+
+        Some(mat4!(
+            ( a22 * c5 - a23 * c4 + a24 * c3) * d,
+            (-a12 * c5 + a13 * c4 - a14 * c3) * d,
+            ( a42 * s5 - a43 * s4 + a44 * s3) * d,
+            (-a32 * s5 + a33 * s4 - a34 * s3) * d,
+
+            (-a21 * c5 + a23 * c2 - a24 * c1) * d,
+            ( a11 * c5 - a13 * c2 + a14 * c1) * d,
+            (-a41 * s5 + a43 * s2 - a44 * s1) * d,
+            ( a31 * s5 - a33 * s2 + a34 * s1) * d,
+
+            ( a21 * c4 - a22 * c2 + a24 * c0) * d,
+            (-a11 * c4 + a12 * c2 - a14 * c0) * d,
+            ( a41 * s4 - a42 * s2 + a44 * s0) * d,
+            (-a31 * s4 + a32 * s2 - a34 * s0) * d,
+
+            (-a21 * c3 + a22 * c1 - a23 * c0) * d,
+            ( a11 * c3 - a12 * c1 + a13 * c0) * d,
+            (-a41 * s3 + a42 * s1 - a43 * s0) * d,
+            ( a31 * s3 - a32 * s1 + a33 * s0) * d
+        ))
+    }
+
+    // Inverse of a matrix whose last row is (0, 0, 0, 1),
+    // i.e. any combination of scaling, rotation and translation.
+    // Returns None when the upper 3x3 is singular.
+    #[allow(dead_code)]
+    #[rustfmt::skip]
+    pub fn inverse_affine(&self) -> Option<Matrix4> {
+        let mat4!(
+            a11, a12, a13, a14,
+            a21, a22, a23, a24,
+            a31, a32, a33, a34,
+            _a41, _a42, _a43, _a44
+        ) = *self;
+
+        let c11 = a22 * a33 - a23 * a32;
+        let c12 = a23 * a31 - a21 * a33;
+        let c13 = a21 * a32 - a22 * a31;
+
+        let det = a11 * c11 + a12 * c12 + a13 * c13;
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        let d = 1.0 / det;
+
+        let b11 = c11 * d;
+        let b12 = (a13 * a32 - a12 * a33) * d;
+        let b13 = (a12 * a23 - a13 * a22) * d;
+        let b21 = c12 * d;
+        let b22 = (a11 * a33 - a13 * a31) * d;
+        let b23 = (a13 * a21 - a11 * a23) * d;
+        let b31 = c13 * d;
+        let b32 = (a12 * a31 - a11 * a32) * d;
+        let b33 = (a11 * a22 - a12 * a21) * d;
+
+        Some(mat4!(
+            b11, b12, b13, -(b11 * a14 + b12 * a24 + b13 * a34),
+            b21, b22, b23, -(b21 * a14 + b22 * a24 + b23 * a34),
+            b31, b32, b33, -(b31 * a14 + b32 * a24 + b33 * a34),
+            0.0, 0.0, 0.0, 1.0
+        ))
+    }
+
+    // Splits an affine matrix into (translation, rotation, scale) so that
+    // `Matrix4::compose(translation, rotation, scale) == self`.
+    // Skew (non-orthogonal axes) cannot be represented and is lost.
+    #[allow(dead_code)]
+    pub fn decompose(&self) -> (Vector3, Quaternion, Vector3) {
+        let mat4!(
+            a11, a12, a13, a14,
+            a21, a22, a23, a24,
+            a31, a32, a33, a34,
+            _a41, _a42, _a43, _a44
+        ) = *self;
+
+        let translation = vec3!(a14, a24, a34);
+
+        let mut sx = vec3!(a11, a21, a31).norm();
+        let sy = vec3!(a12, a22, a32).norm();
+        let sz = vec3!(a13, a23, a33).norm();
+
+        // A mirrored basis is folded into a negative X scale.
+        if self.determinant() < 0.0 {
+            sx = -sx;
+        }
+
+        let rotation = Quaternion::from(mat4!(
+            a11 / sx, a12 / sy, a13 / sz, 0.0,
+            a21 / sx, a22 / sy, a23 / sz, 0.0,
+            a31 / sx, a32 / sy, a33 / sz, 0.0,
+            0.0, 0.0, 0.0, 1.0
+        ));
+
+        (translation, rotation, vec3!(sx, sy, sz))
+    }
+
+    #[allow(dead_code)]
+    pub fn compose(translation: Vector3, rotation: Quaternion, scale: Vector3) -> Matrix4 {
+        let vec3!(tx, ty, tz) = translation;
+        let vec3!(sx, sy, sz) = scale;
+        Self::translation(tx, ty, tz) * Matrix4::from(rotation) * Self::scaling(sx, sy, sz)
+    }
+
+    // Turns a canvas pixel into a world-space ray, where self is the
+    // projection-view matrix. Pixel coordinates are the same as Sprite uses:
+    // (0, 0) is the top left corner and y goes down.
+    // Returns None when the matrix is not invertible.
+    #[allow(dead_code)]
+    pub fn unproject(&self, screen_point: Vector2, viewport: Size) -> Option<Ray> {
+        let inverse = self.inverse()?;
+        let x = 2.0 * screen_point.x / viewport.w as f32 - 1.0;
+        let y = 1.0 - 2.0 * screen_point.y / viewport.h as f32;
+
+        let point = |z: f32| {
+            let v = inverse * vec4!(x, y, z, 1.0);
+            v.xyz() / v.w
+        };
+
+        // Points in front get larger clip w. That tells which end of the
+        // NDC depth range is near, so reverse-Z projections work too.
+        let a = point(0.0);
+        let b = point(0.5);
+        let w = |p: Vector3| (*self * Vector4::from(p)).w;
+        let reverse_z = w(b) < w(a);
+
+        let (origin, direction) = if reverse_z {
+            (point(1.0), a - b)
+        } else {
+            (point(-1.0), b - a)
+        };
+        Some(Ray::new(origin, direction))
+    }
 }
 
 #[macro_export]
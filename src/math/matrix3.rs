@@ -0,0 +1,237 @@
+use std::{mem::size_of, ops::Mul};
+
+use crate::{mat3, mat4, vec2, vec3};
+
+use super::{Matrix4, Vector2, Vector3};
+
+// 3x3 matrix. Used either as the linear part of a Matrix4 (normal matrix)
+// or as a 2D affine transform acting on (x, y, 1).
+#[derive(Debug, Copy, Clone)]
+#[repr(C)]
+pub struct Matrix3 {
+    pub m11: f32,
+    pub m12: f32,
+    pub m13: f32,
+    pub m21: f32,
+    pub m22: f32,
+    pub m23: f32,
+    pub m31: f32,
+    pub m32: f32,
+    pub m33: f32,
+}
+
+impl PartialEq for Matrix3 {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_ref() == other.as_ref()
+    }
+}
+
+impl Mul for Matrix3 {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        let mat3!(
+            a11, a12, a13,
+            a21, a22, a23,
+            a31, a32, a33
+        ) = self;
+        let mat3!(
+            b11, b12, b13,
+            b21, b22, b23,
+            b31, b32, b33
+        ) = rhs;
+        mat3!(
+            a11*b11 + a12*b21 + a13*b31,
+            a11*b12 + a12*b22 + a13*b32,
+            a11*b13 + a12*b23 + a13*b33,
+            a21*b11 + a22*b21 + a23*b31,
+            a21*b12 + a22*b22 + a23*b32,
+            a21*b13 + a22*b23 + a23*b33,
+            a31*b11 + a32*b21 + a33*b31,
+            a31*b12 + a32*b22 + a33*b32,
+            a31*b13 + a32*b23 + a33*b33
+        )
+    }
+}
+
+impl Mul<Vector3> for Matrix3 {
+    type Output = Vector3;
+
+    fn mul(self, rhs: Vector3) -> Self::Output {
+        let mat3!(
+            a11, a12, a13,
+            a21, a22, a23,
+            a31, a32, a33
+        ) = self;
+        let vec3!(x, y, z) = rhs;
+        vec3!(
+            a11*x + a12*y + a13*z,
+            a21*x + a22*y + a23*z,
+            a31*x + a32*y + a33*z)
+    }
+}
+
+// Transforms a 2D point, i.e. (x, y, 1).
+impl Mul<Vector2> for Matrix3 {
+    type Output = Vector2;
+
+    fn mul(self, rhs: Vector2) -> Self::Output {
+        let mat3!(
+            a11, a12, a13,
+            a21, a22, a23,
+            a31, a32, a33
+        ) = self;
+        let vec2!(x, y) = rhs;
+        let w = 1.0/(a31*x + a32*y + a33);
+        vec2!(
+            (a11*x + a12*y + a13) * w,
+            (a21*x + a22*y + a23) * w)
+    }
+}
+
+impl AsRef<[f32]> for Matrix3 {
+    fn as_ref(&self) -> &[f32] {
+        unsafe {
+          std::slice::from_raw_parts(&self.m11 as *const f32, size_of::<Self>() / size_of::<f32>())
+        }
+    }
+}
+
+// Upper left 3x3 of the matrix.
+impl From<Matrix4> for Matrix3 {
+    fn from(value: Matrix4) -> Self {
+        let mat4!(
+            a11, a12, a13, _a14,
+            a21, a22, a23, _a24,
+            a31, a32, a33, _a34,
+            _a41, _a42, _a43, _a44
+        ) = value;
+        mat3!(
+            a11, a12, a13,
+            a21, a22, a23,
+            a31, a32, a33
+        )
+    }
+}
+
+#[allow(dead_code)]
+impl Matrix3 {
+    pub const IDENT: Matrix3 = mat3!(
+      1.0, 0.0, 0.0,
+      0.0, 1.0, 0.0,
+      0.0, 0.0, 1.0
+    );
+
+    pub fn transpose(self) -> Matrix3 {
+        let mat3!(
+            a11, a12, a13,
+            a21, a22, a23,
+            a31, a32, a33
+        ) = self;
+        mat3!(
+            a11, a21, a31,
+            a12, a22, a32,
+            a13, a23, a33
+        )
+    }
+
+    pub fn determinant(&self) -> f32 {
+        let mat3!(
+            a11, a12, a13,
+            a21, a22, a23,
+            a31, a32, a33
+        ) = *self;
+        a11 * (a22 * a33 - a23 * a32)
+            - a12 * (a21 * a33 - a23 * a31)
+            + a13 * (a21 * a32 - a22 * a31)
+    }
+
+    // Returns None when the matrix is singular.
+    pub fn inverse(&self) -> Option<Matrix3> {
+        let mat3!(
+            a11, a12, a13,
+            a21, a22, a23,
+            a31, a32, a33
+        ) = *self;
+
+        let det = self.determinant();
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        let d = 1.0 / det;
+
+        Some(mat3!(
+            (a22 * a33 - a23 * a32) * d,
+            (a13 * a32 - a12 * a33) * d,
+            (a12 * a23 - a13 * a22) * d,
+            (a23 * a31 - a21 * a33) * d,
+            (a11 * a33 - a13 * a31) * d,
+            (a13 * a21 - a11 * a23) * d,
+            (a21 * a32 - a22 * a31) * d,
+            (a12 * a31 - a11 * a32) * d,
+            (a11 * a22 - a12 * a21) * d
+        ))
+    }
+
+    // Inverse-transpose of the upper 3x3 of the model (or model-view) matrix.
+    // Transforms normals so they stay perpendicular under non-uniform scaling.
+    pub fn normal_matrix(model: Matrix4) -> Option<Matrix3> {
+        Matrix3::from(model).inverse().map(Matrix3::transpose)
+    }
+
+    pub fn translation(x: f32, y: f32) -> Matrix3 {
+        mat3!(
+          1.0, 0.0, x,
+          0.0, 1.0, y,
+          0.0, 0.0, 1.0
+        )
+    }
+
+    #[rustfmt::skip]
+    pub fn rotation(r: f32) -> Matrix3 {
+        mat3!(
+          r.cos(), -r.sin(), 0.0,
+          r.sin(),  r.cos(), 0.0,
+              0.0,      0.0, 1.0
+        )
+    }
+
+    pub fn scaling(x: f32, y: f32) -> Matrix3 {
+        mat3!(
+          x, 0.0, 0.0,
+          0.0, y, 0.0,
+          0.0, 0.0, 1.0
+        )
+    }
+
+    // Shears x along y by angle_x and y along x by angle_y.
+    pub fn skew(angle_x: f32, angle_y: f32) -> Matrix3 {
+        mat3!(
+          1.0, angle_x.tan(), 0.0,
+          angle_y.tan(), 1.0, 0.0,
+          0.0, 0.0, 1.0
+        )
+    }
+}
+
+#[macro_export]
+macro_rules! mat3 {
+    (
+        $a11:ident, $a12:ident, $a13:ident,
+        $a21:ident, $a22:ident, $a23:ident,
+        $a31:ident, $a32:ident, $a33:ident
+    ) => ($crate::math::Matrix3 {
+        m11: $a11, m12: $a12, m13: $a13,
+        m21: $a21, m22: $a22, m23: $a23,
+        m31: $a31, m32: $a32, m33: $a33
+    });
+    (
+        $a11:expr, $a12:expr, $a13:expr,
+        $a21:expr, $a22:expr, $a23:expr,
+        $a31:expr, $a32:expr, $a33:expr
+    ) => ($crate::math::Matrix3 {
+        m11: $a11, m12: $a12, m13: $a13,
+        m21: $a21, m22: $a22, m23: $a23,
+        m31: $a31, m32: $a32, m33: $a33
+    })
+}
@@ -1,4 +1,211 @@
+use std::ops::{Mul, Neg};
+
+use crate::{mat4, vec3, vec4};
+
 use super::*;
 
-pub struct Quaternion(Vector4);
+// Rotation quaternion stored as (x, y, z, w) where w is the scalar part.
+//
+// Multiplication follows the same order as Matrix4:
+// `(a * b).rotate(v)` rotates v by b first, then by a.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[repr(C)]
+pub struct Quaternion(pub Vector4);
+
+#[allow(dead_code)]
+impl Quaternion {
+    pub const IDENT: Quaternion = Quaternion(vec4!(0.0, 0.0, 0.0, 1.0));
+
+    pub fn from_axis_angle(axis: Vector3, angle: f32) -> Quaternion {
+        let vec3!(x, y, z) = axis.normalize();
+        let (s, c) = (angle / 2.0).sin_cos();
+        Quaternion(vec4!(x * s, y * s, z * s, c))
+    }
+
+    pub fn pitch_rotation(r: f32) -> Quaternion {
+        let (s, c) = (r / 2.0).sin_cos();
+        Quaternion(vec4!(s, 0.0, 0.0, c))
+    }
+
+    pub fn yaw_rotation(r: f32) -> Quaternion {
+        let (s, c) = (r / 2.0).sin_cos();
+        Quaternion(vec4!(0.0, s, 0.0, c))
+    }
+
+    pub fn roll_rotation(r: f32) -> Quaternion {
+        let (s, c) = (r / 2.0).sin_cos();
+        Quaternion(vec4!(0.0, 0.0, s, c))
+    }
+
+    // Same rotation order as Matrix4::rotation (pitch, then yaw, then roll).
+    pub fn from_euler(pitch: f32, yaw: f32, roll: f32) -> Quaternion {
+        Self::roll_rotation(roll) * Self::yaw_rotation(yaw) * Self::pitch_rotation(pitch)
+    }
+
+    pub fn dot(self, rhs: Quaternion) -> f32 {
+        let Quaternion(vec4!(ax, ay, az, aw)) = self;
+        let Quaternion(vec4!(bx, by, bz, bw)) = rhs;
+        ax * bx + ay * by + az * bz + aw * bw
+    }
+
+    pub fn norm(self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    pub fn normalize(self) -> Quaternion {
+        let n = self.norm();
+        let Quaternion(vec4!(x, y, z, w)) = self;
+        Quaternion(vec4!(x / n, y / n, z / n, w / n))
+    }
+
+    pub fn conjugate(self) -> Quaternion {
+        let Quaternion(vec4!(x, y, z, w)) = self;
+        Quaternion(vec4!(-x, -y, -z, w))
+    }
+
+    pub fn inverse(self) -> Quaternion {
+        let n = self.dot(self);
+        let Quaternion(vec4!(x, y, z, w)) = self.conjugate();
+        Quaternion(vec4!(x / n, y / n, z / n, w / n))
+    }
+
+    // Returns (axis, angle). The axis of the identity rotation is +X.
+    pub fn to_axis_angle(self) -> (Vector3, f32) {
+        let Quaternion(vec4!(x, y, z, w)) = self.normalize();
+        let s = (1.0 - w * w).max(0.0).sqrt();
+        if s < f32::EPSILON {
+            return (vec3!(1.0, 0.0, 0.0), 0.0);
+        }
+        (vec3!(x / s, y / s, z / s), 2.0 * w.clamp(-1.0, 1.0).acos())
+    }
+
+    // Rotates v by this quaternion. Assumes the quaternion is normalized.
+    pub fn rotate(self, v: Vector3) -> Vector3 {
+        let Quaternion(vec4!(x, y, z, w)) = self;
+        let u = vec3!(x, y, z);
+        let t = u.cross(v) * 2.0;
+        v + t * w + u.cross(t)
+    }
+
+    // Normalized linear interpolation. Takes the shortest path.
+    pub fn nlerp(self, rhs: Quaternion, t: f32) -> Quaternion {
+        let rhs = if self.dot(rhs) < 0.0 { -rhs } else { rhs };
+        let Quaternion(vec4!(ax, ay, az, aw)) = self;
+        let Quaternion(vec4!(bx, by, bz, bw)) = rhs;
+        Quaternion(vec4!(
+            ax + (bx - ax) * t,
+            ay + (by - ay) * t,
+            az + (bz - az) * t,
+            aw + (bw - aw) * t
+        ))
+        .normalize()
+    }
+
+    // Spherical linear interpolation. Takes the shortest path.
+    pub fn slerp(self, rhs: Quaternion, t: f32) -> Quaternion {
+        let mut cos = self.dot(rhs);
+        let rhs = if cos < 0.0 {
+            cos = -cos;
+            -rhs
+        } else {
+            rhs
+        };
+
+        // sin(theta) gets too close to zero to divide by.
+        if cos > 0.9995 {
+            return self.nlerp(rhs, t);
+        }
+
+        let theta = cos.acos();
+        let sin = theta.sin();
+        let a = ((1.0 - t) * theta).sin() / sin;
+        let b = (t * theta).sin() / sin;
+
+        let Quaternion(vec4!(ax, ay, az, aw)) = self;
+        let Quaternion(vec4!(bx, by, bz, bw)) = rhs;
+        Quaternion(vec4!(
+            ax * a + bx * b,
+            ay * a + by * b,
+            az * a + bz * b,
+            aw * a + bw * b
+        ))
+    }
+}
+
+impl Mul for Quaternion {
+    type Output = Quaternion;
+
+    fn mul(self, rhs: Quaternion) -> Self::Output {
+        let Quaternion(vec4!(ax, ay, az, aw)) = self;
+        let Quaternion(vec4!(bx, by, bz, bw)) = rhs;
+        Quaternion(vec4!(
+            aw * bx + ax * bw + ay * bz - az * by,
+            aw * by - ax * bz + ay * bw + az * bx,
+            aw * bz + ax * by - ay * bx + az * bw,
+            aw * bw - ax * bx - ay * by - az * bz
+        ))
+    }
+}
+
+impl Mul<Vector3> for Quaternion {
+    type Output = Vector3;
+
+    fn mul(self, rhs: Vector3) -> Self::Output {
+        self.rotate(rhs)
+    }
+}
+
+impl Neg for Quaternion {
+    type Output = Quaternion;
+
+    fn neg(self) -> Self::Output {
+        let Quaternion(vec4!(x, y, z, w)) = self;
+        Quaternion(vec4!(-x, -y, -z, -w))
+    }
+}
+
+impl From<Quaternion> for Matrix4 {
+    #[rustfmt::skip]
+    fn from(value: Quaternion) -> Self {
+        let Quaternion(vec4!(x, y, z, w)) = value;
+        let (xx, yy, zz) = (x * x, y * y, z * z);
+        let (xy, xz, yz) = (x * y, x * z, y * z);
+        let (wx, wy, wz) = (w * x, w * y, w * z);
+
+        mat4!(
+            1.0 - 2.0 * (yy + zz),       2.0 * (xy - wz),       2.0 * (xz + wy), 0.0,
+                  2.0 * (xy + wz), 1.0 - 2.0 * (xx + zz),       2.0 * (yz - wx), 0.0,
+                  2.0 * (xz - wy),       2.0 * (yz + wx), 1.0 - 2.0 * (xx + yy), 0.0,
+                              0.0,                   0.0,                   0.0, 1.0
+        )
+    }
+}
+
+// Extracts the rotation from the upper 3x3 of a matrix without scaling.
+impl From<Matrix4> for Quaternion {
+    fn from(value: Matrix4) -> Self {
+        let Matrix4 {
+            m11, m12, m13,
+            m21, m22, m23,
+            m31, m32, m33,
+            ..
+        } = value;
+        let trace = m11 + m22 + m33;
+
+        let q = if trace > 0.0 {
+            let s = (trace + 1.0).sqrt() * 2.0;
+            vec4!((m32 - m23) / s, (m13 - m31) / s, (m21 - m12) / s, s / 4.0)
+        } else if m11 > m22 && m11 > m33 {
+            let s = (1.0 + m11 - m22 - m33).sqrt() * 2.0;
+            vec4!(s / 4.0, (m12 + m21) / s, (m13 + m31) / s, (m32 - m23) / s)
+        } else if m22 > m33 {
+            let s = (1.0 + m22 - m11 - m33).sqrt() * 2.0;
+            vec4!((m12 + m21) / s, s / 4.0, (m23 + m32) / s, (m13 - m31) / s)
+        } else {
+            let s = (1.0 + m33 - m11 - m22).sqrt() * 2.0;
+            vec4!((m13 + m31) / s, (m23 + m32) / s, s / 4.0, (m21 - m12) / s)
+        };
 
+        Quaternion(q).normalize()
+    }
+}
@@ -0,0 +1,121 @@
+use crate::vec3;
+
+use super::{Aabb, BoundingSphere, Plane, Vector3};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Ray {
+    pub origin: Vector3,
+    // Unit length.
+    pub direction: Vector3,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TriangleHit {
+    pub distance: f32,
+    // Weights of the triangle vertices (a, b, c) at the hit point.
+    pub barycentric: Vector3,
+}
+
+// All intersection tests return the distance along the ray to the nearest hit
+// in front of the origin. A ray starting inside a volume hits at distance 0.
+#[allow(dead_code)]
+impl Ray {
+    pub fn new(origin: Vector3, direction: Vector3) -> Ray {
+        Ray { origin, direction: direction.normalize() }
+    }
+
+    pub fn at(&self, distance: f32) -> Vector3 {
+        self.origin + self.direction * distance
+    }
+
+    pub fn intersect_plane(&self, plane: &Plane) -> Option<f32> {
+        let denom = plane.normal.dot(self.direction);
+        if denom.abs() < f32::EPSILON {
+            return None;
+        }
+        let t = -plane.distance(self.origin) / denom;
+        if t < 0.0 {
+            return None;
+        }
+        Some(t)
+    }
+
+    // Slab method.
+    pub fn intersect_aabb(&self, aabb: &Aabb) -> Option<f32> {
+        let vec3!(ox, oy, oz) = self.origin;
+        let vec3!(dx, dy, dz) = self.direction;
+        let axes = [
+            (ox, dx, aabb.min.x, aabb.max.x),
+            (oy, dy, aabb.min.y, aabb.max.y),
+            (oz, dz, aabb.min.z, aabb.max.z),
+        ];
+
+        let mut t_min = 0.0f32;
+        let mut t_max = f32::INFINITY;
+        for (o, d, min, max) in axes {
+            if d.abs() < f32::EPSILON {
+                if o < min || o > max {
+                    return None;
+                }
+                continue;
+            }
+            let t1 = (min - o) / d;
+            let t2 = (max - o) / d;
+            t_min = t_min.max(t1.min(t2));
+            t_max = t_max.min(t1.max(t2));
+            if t_min > t_max {
+                return None;
+            }
+        }
+        Some(t_min)
+    }
+
+    pub fn intersect_sphere(&self, sphere: &BoundingSphere) -> Option<f32> {
+        let oc = self.origin - sphere.center;
+        let b = oc.dot(self.direction);
+        let c = oc.dot(oc) - sphere.radius * sphere.radius;
+        let discriminant = b * b - c;
+        if discriminant < 0.0 {
+            return None;
+        }
+        let sqrt = discriminant.sqrt();
+        if -b + sqrt < 0.0 {
+            return None;
+        }
+        Some((-b - sqrt).max(0.0))
+    }
+
+    // Möller–Trumbore. Hits both faces of the triangle.
+    pub fn intersect_triangle(&self, a: Vector3, b: Vector3, c: Vector3) -> Option<TriangleHit> {
+        let ab = b - a;
+        let ac = c - a;
+        let p = self.direction.cross(ac);
+        let det = ab.dot(p);
+        if det.abs() < f32::EPSILON {
+            return None;
+        }
+        let inv_det = 1.0 / det;
+
+        let ao = self.origin - a;
+        let u = ao.dot(p) * inv_det;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let q = ao.cross(ab);
+        let v = self.direction.dot(q) * inv_det;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let distance = ac.dot(q) * inv_det;
+        if distance < 0.0 {
+            return None;
+        }
+
+        Some(TriangleHit {
+            distance,
+            barycentric: vec3!(1.0 - u - v, u, v),
+        })
+    }
+}
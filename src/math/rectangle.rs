@@ -1,6 +1,6 @@
-use crate::vec4;
+use crate::{vec2, vec4};
 
-use super::Vector4;
+use super::{Vector2, Vector4};
 
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -11,6 +11,15 @@ pub struct Rectangle {
     pub h: i32
 }
 
+// Rectangle with float coordinates, for sub-pixel positions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RectF {
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Size {
     pub w: i32,
@@ -31,6 +40,20 @@ macro_rules! rect {
     });
 }
 
+#[macro_export]
+macro_rules! rectf {
+    (
+        $x:ident, $y:ident, $w:ident, $h:ident
+    ) => ($crate::math::RectF {
+        x: $x, y: $y, w: $w, h: $h
+    });
+    (
+        $x:expr, $y:expr, $w:expr, $h:expr
+    ) => ($crate::math::RectF {
+        x: $x, y: $y, w: $w, h: $h
+    });
+}
+
 #[macro_export]
 macro_rules! size {
     (
@@ -69,4 +92,212 @@ impl From<Rectangle> for Vector4 {
             rect!(x, y, z, w) => vec4!(x as f32, y as f32, z as f32, w as f32)
         }  
     }
-}
\ No newline at end of file
+}
+
+// Edges are (x, y) inclusive and (x + w, y + h) exclusive.
+// Rectangles with non-positive width or height are empty.
+#[allow(dead_code)]
+impl Rectangle {
+    pub fn left(&self) -> i32 {
+        self.x
+    }
+
+    pub fn top(&self) -> i32 {
+        self.y
+    }
+
+    pub fn right(&self) -> i32 {
+        self.x + self.w
+    }
+
+    pub fn bottom(&self) -> i32 {
+        self.y + self.h
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.w <= 0 || self.h <= 0
+    }
+
+    pub fn contains(&self, point: (i32, i32)) -> bool {
+        let (x, y) = point;
+        x >= self.left() && x < self.right() && y >= self.top() && y < self.bottom()
+    }
+
+    // Returns None when the rectangles do not overlap.
+    pub fn intersection(&self, other: &Rectangle) -> Option<Rectangle> {
+        let left = self.left().max(other.left());
+        let top = self.top().max(other.top());
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+        let r = rect!(left, top, right - left, bottom - top);
+        if r.is_empty() {
+            return None;
+        }
+        Some(r)
+    }
+
+    // Smallest rectangle containing both. Empty rectangles are ignored.
+    pub fn union(&self, other: &Rectangle) -> Rectangle {
+        if other.is_empty() {
+            return *self;
+        }
+        if self.is_empty() {
+            return *other;
+        }
+        let left = self.left().min(other.left());
+        let top = self.top().min(other.top());
+        let right = self.right().max(other.right());
+        let bottom = self.bottom().max(other.bottom());
+        rect!(left, top, right - left, bottom - top)
+    }
+
+    // Grows by dx on the left and right, and by dy on the top and bottom.
+    // Negative values shrink.
+    pub fn inflate(&self, dx: i32, dy: i32) -> Rectangle {
+        rect!(self.x - dx, self.y - dy, self.w + dx * 2, self.h + dy * 2)
+    }
+
+    pub fn offset(&self, dx: i32, dy: i32) -> Rectangle {
+        rect!(self.x + dx, self.y + dy, self.w, self.h)
+    }
+
+    // Splits into (left, right) where left is `width` wide (clamped to the rectangle).
+    pub fn split_horizontally(&self, width: i32) -> (Rectangle, Rectangle) {
+        let width = width.max(0).min(self.w);
+        (
+            rect!(self.x, self.y, width, self.h),
+            rect!(self.x + width, self.y, self.w - width, self.h)
+        )
+    }
+
+    // Splits into (top, bottom) where top is `height` tall (clamped to the rectangle).
+    pub fn split_vertically(&self, height: i32) -> (Rectangle, Rectangle) {
+        let height = height.max(0).min(self.h);
+        (
+            rect!(self.x, self.y, self.w, height),
+            rect!(self.x, self.y + height, self.w, self.h - height)
+        )
+    }
+}
+
+// Edges are (x, y) inclusive and (x + w, y + h) exclusive.
+// Rectangles with non-positive width or height are empty.
+#[allow(dead_code)]
+impl RectF {
+    pub fn left(&self) -> f32 {
+        self.x
+    }
+
+    pub fn top(&self) -> f32 {
+        self.y
+    }
+
+    pub fn right(&self) -> f32 {
+        self.x + self.w
+    }
+
+    pub fn bottom(&self) -> f32 {
+        self.y + self.h
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.w <= 0.0 || self.h <= 0.0
+    }
+
+    pub fn contains(&self, point: Vector2) -> bool {
+        let vec2!(x, y) = point;
+        x >= self.left() && x < self.right() && y >= self.top() && y < self.bottom()
+    }
+
+    // Returns None when the rectangles do not overlap.
+    pub fn intersection(&self, other: &RectF) -> Option<RectF> {
+        let left = self.left().max(other.left());
+        let top = self.top().max(other.top());
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+        let r = rectf!(left, top, right - left, bottom - top);
+        if r.is_empty() {
+            return None;
+        }
+        Some(r)
+    }
+
+    // Smallest rectangle containing both. Empty rectangles are ignored.
+    pub fn union(&self, other: &RectF) -> RectF {
+        if other.is_empty() {
+            return *self;
+        }
+        if self.is_empty() {
+            return *other;
+        }
+        let left = self.left().min(other.left());
+        let top = self.top().min(other.top());
+        let right = self.right().max(other.right());
+        let bottom = self.bottom().max(other.bottom());
+        rectf!(left, top, right - left, bottom - top)
+    }
+
+    // Grows by dx on the left and right, and by dy on the top and bottom.
+    // Negative values shrink.
+    pub fn inflate(&self, dx: f32, dy: f32) -> RectF {
+        rectf!(self.x - dx, self.y - dy, self.w + dx * 2.0, self.h + dy * 2.0)
+    }
+
+    pub fn offset(&self, dx: f32, dy: f32) -> RectF {
+        rectf!(self.x + dx, self.y + dy, self.w, self.h)
+    }
+
+    // Splits into (left, right) where left is `width` wide (clamped to the rectangle).
+    pub fn split_horizontally(&self, width: f32) -> (RectF, RectF) {
+        let width = width.max(0.0).min(self.w);
+        (
+            rectf!(self.x, self.y, width, self.h),
+            rectf!(self.x + width, self.y, self.w - width, self.h)
+        )
+    }
+
+    // Splits into (top, bottom) where top is `height` tall (clamped to the rectangle).
+    pub fn split_vertically(&self, height: f32) -> (RectF, RectF) {
+        let height = height.max(0.0).min(self.h);
+        (
+            rectf!(self.x, self.y, self.w, height),
+            rectf!(self.x, self.y + height, self.w, self.h - height)
+        )
+    }
+}
+
+#[allow(dead_code)]
+impl RectF {
+    // Nearest integer rectangle; each edge is rounded independently.
+    pub fn round(&self) -> Rectangle {
+        let left = self.left().round() as i32;
+        let top = self.top().round() as i32;
+        let right = self.right().round() as i32;
+        let bottom = self.bottom().round() as i32;
+        rect!(left, top, right - left, bottom - top)
+    }
+
+    // Smallest integer rectangle containing this one.
+    pub fn round_out(&self) -> Rectangle {
+        let left = self.left().floor() as i32;
+        let top = self.top().floor() as i32;
+        let right = self.right().ceil() as i32;
+        let bottom = self.bottom().ceil() as i32;
+        rect!(left, top, right - left, bottom - top)
+    }
+}
+
+// Exact as long as the coordinates fit in f32's 24-bit mantissa.
+impl From<Rectangle> for RectF {
+    fn from(value: Rectangle) -> Self {
+        let rect!(x, y, w, h) = value;
+        rectf!(x as f32, y as f32, w as f32, h as f32)
+    }
+}
+
+impl From<RectF> for Vector4 {
+    fn from(val: RectF) -> Self {
+        let rectf!(x, y, w, h) = val;
+        vec4!(x, y, w, h)
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{mat4, math::Matrix4};
+use crate::{assert_approx_eq, mat4, math::{curve::{self, ArcLength, CatmullRom, CubicBezier, Curve, Hermite}, Aabb, ApproxEq, BoundingSphere, Color, Frustum, Matrix3, Matrix4, Plane, Quaternion, Ray, RectF, TriangleHit, Vector3, Vector4}, rect, rectf, size, vec2, vec3, vec4};
 
 #[test]
 fn test_scaling() {
@@ -10,4 +10,453 @@ fn test_scaling() {
       0.0, 0.0, 56.0, 0.0,
       0.0, 0.0, 0.0, 1.0
     ));
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_quaternion_matches_euler_matrix() {
+  let (pitch, yaw, roll) = (0.3, -1.2, 2.1);
+  assert_approx_eq!(
+    Matrix4::from(Quaternion::from_euler(pitch, yaw, roll)),
+    Matrix4::rotation(pitch, yaw, roll));
+}
+
+#[test]
+fn test_quaternion_matrix_round_trip() {
+  let q = Quaternion::from_axis_angle(vec3!(1.0, 2.0, -0.5), 2.5);
+  let p = Quaternion::from(Matrix4::from(q));
+  assert_approx_eq!(p, q);
+}
+
+#[test]
+fn test_quaternion_rotate() {
+  let q = Quaternion::from_axis_angle(vec3!(0.0, 0.0, 1.0), std::f32::consts::FRAC_PI_2);
+  assert_approx_eq!(q * vec3!(1.0, 0.0, 0.0), vec3!(0.0, 1.0, 0.0));
+  assert_approx_eq!(q.inverse() * (q * vec3!(3.0, -1.0, 2.0)), vec3!(3.0, -1.0, 2.0));
+}
+
+#[test]
+fn test_quaternion_slerp() {
+  let a = Quaternion::yaw_rotation(0.0);
+  let b = Quaternion::yaw_rotation(1.0);
+  let (axis, angle) = a.slerp(b, 0.25).to_axis_angle();
+  assert_approx_eq!(axis, vec3!(0.0, 1.0, 0.0));
+  assert_approx_eq!(angle, 0.25);
+}
+
+#[test]
+fn test_inverse() {
+  let m = Matrix4::perspective_fov(1.0, 1.5, 0.1, 100.0)
+    * Matrix4::look_at(vec3!(1.0, 2.0, 3.0), vec3!(0.0, 0.0, 0.0), vec3!(0.0, 1.0, 0.0));
+  assert_approx_eq!(m * m.inverse().unwrap(), Matrix4::IDENT);
+  assert!((m.determinant() * m.inverse().unwrap().determinant() - 1.0).abs() < 1e-4);
+  assert_eq!(Matrix4::scaling(1.0, 0.0, 1.0).inverse(), None);
+}
+
+#[test]
+fn test_inverse_affine() {
+  let m = Matrix4::translation(1.0, -2.0, 3.0) * Matrix4::rotation(0.4, 0.5, 0.6) * Matrix4::scaling(2.0, 3.0, 4.0);
+  assert_approx_eq!(m.inverse_affine().unwrap(), m.inverse().unwrap());
+}
+
+#[test]
+fn test_decompose() {
+  let rotation = Quaternion::from_euler(0.4, -0.5, 0.6);
+  let m = Matrix4::compose(vec3!(1.0, -2.0, 3.0), rotation, vec3!(2.0, 3.0, 4.0));
+  let (t, r, s) = m.decompose();
+  assert_approx_eq!(t, vec3!(1.0, -2.0, 3.0));
+  assert_approx_eq!(s, vec3!(2.0, 3.0, 4.0));
+  assert_approx_eq!(r, rotation);
+
+  let mirrored = Matrix4::compose(vec3!(0.0, 0.0, 0.0), rotation, vec3!(2.0, -3.0, 4.0));
+  let (t, r, s) = mirrored.decompose();
+  assert_approx_eq!(Matrix4::compose(t, r, s), mirrored);
+}
+
+fn project_depth(m: Matrix4, z: f32) -> f32 {
+  (m * vec3!(0.0, 0.0, z)).z
+}
+
+#[test]
+fn test_perspective_depth_mapping() {
+  let standard = Matrix4::perspective_fov(1.0, 1.0, 0.5, 100.0);
+  assert_approx_eq!(project_depth(standard, -0.5), -1.0);
+  assert_approx_eq!(project_depth(standard, -100.0), 1.0);
+
+  let reverse = Matrix4::perspective_reverse_z(1.0, 1.0, 0.5, 100.0);
+  assert_approx_eq!(project_depth(reverse, -0.5), 1.0);
+  assert_approx_eq!(project_depth(reverse, -100.0), -1.0);
+
+  let infinite = Matrix4::perspective_infinite(1.0, 1.0, 0.5);
+  assert_approx_eq!(project_depth(infinite, -0.5), -1.0);
+  assert_approx_eq!(project_depth(infinite, -1.0e6), 1.0);
+
+  let infinite_reverse = Matrix4::perspective_infinite_reverse_z(1.0, 1.0, 0.5);
+  assert_approx_eq!(project_depth(infinite_reverse, -0.5), 1.0);
+  assert_approx_eq!(project_depth(infinite_reverse, -1.0e6), -1.0);
+}
+
+#[test]
+fn test_orthographic() {
+  let m = Matrix4::orthographic(0.0, 640.0, 480.0, 0.0, -1.0, 1.0);
+  assert_approx_eq!(m * vec3!(0.0, 0.0, 1.0), vec3!(-1.0, 1.0, -1.0));
+  assert_approx_eq!(m * vec3!(640.0, 480.0, -1.0), vec3!(1.0, -1.0, 1.0));
+}
+
+#[test]
+fn test_vector_arithmetic() {
+  let a = vec4!(1.0, 2.0, 3.0, 4.0);
+  let b = vec4!(4.0, 3.0, 2.0, 1.0);
+  assert_eq!(a + b, vec4!(5.0, 5.0, 5.0, 5.0));
+  assert_eq!(a * b, vec4!(4.0, 6.0, 6.0, 4.0));
+  assert_eq!(a.lerp(b, 0.5), vec4!(2.5, 2.5, 2.5, 2.5));
+  assert_eq!(a.min(b), vec4!(1.0, 2.0, 2.0, 1.0));
+  assert_eq!(a.clamp(vec4!(2.0, 2.0, 2.0, 2.0), vec4!(3.0, 3.0, 3.0, 3.0)), vec4!(2.0, 2.0, 3.0, 3.0));
+  assert_eq!(vec2!(3.0, 4.0).length(), 5.0);
+  assert_eq!(-vec2!(3.0, 4.0) / 2.0, vec2!(-1.5, -2.0));
+}
+
+#[test]
+fn test_vector_swizzle() {
+  let v = vec4!(1.0, 2.0, 3.0, 4.0);
+  assert_eq!(v.xy(), vec2!(1.0, 2.0));
+  assert_eq!(v.zw(), vec2!(3.0, 4.0));
+  assert_eq!(v.xyz(), vec3!(1.0, 2.0, 3.0));
+  assert_eq!(v.zyx(), vec3!(3.0, 2.0, 1.0));
+  assert_eq!(v.xyz().xy().yx(), vec2!(2.0, 1.0));
+}
+
+#[test]
+fn test_matrix_vector4() {
+  let m = Matrix4::translation(1.0, 2.0, 3.0);
+  assert_eq!(m * vec4!(1.0, 1.0, 1.0, 1.0), vec4!(2.0, 3.0, 4.0, 1.0));
+  assert_eq!(m * vec4!(1.0, 1.0, 1.0, 0.0), vec4!(1.0, 1.0, 1.0, 0.0));
+}
+
+#[test]
+fn test_normal_matrix() {
+  let model = Matrix4::rotation(0.3, 0.2, 0.1) * Matrix4::scaling(1.0, 4.0, 1.0);
+  let normal = Matrix3::normal_matrix(model).unwrap();
+
+  // A tangent on the surface stays perpendicular to the transformed normal.
+  let n = vec3!(0.0, 1.0, 1.0).normalize();
+  let t = vec3!(0.0, 1.0, -1.0).normalize();
+  assert!((normal * n).dot(Matrix3::from(model) * t).abs() < 1e-5);
+}
+
+#[test]
+fn test_matrix3_affine() {
+  let m = Matrix3::translation(10.0, 20.0) * Matrix3::rotation(std::f32::consts::FRAC_PI_2) * Matrix3::scaling(2.0, 2.0);
+  let p = m * vec2!(1.0, 0.0);
+  assert_approx_eq!(p, vec2!(10.0, 22.0));
+  assert_eq!(Matrix3::skew(std::f32::consts::FRAC_PI_4, 0.0) * vec2!(0.0, 1.0), vec2!(1.0, 1.0));
+  assert_approx_eq!(m * m.inverse().unwrap(), Matrix3::IDENT);
+}
+
+#[test]
+fn test_frustum_culling() {
+  let pv = Matrix4::perspective_reverse_z(1.0, 1.0, 0.1, 100.0)
+    * Matrix4::look_at(vec3!(0.0, 0.0, 10.0), vec3!(0.0, 0.0, 0.0), vec3!(0.0, 1.0, 0.0));
+  let frustum = Frustum::from(pv);
+
+  let unit = Aabb::new(vec3!(-0.5, -0.5, -0.5), vec3!(0.5, 0.5, 0.5));
+  assert!(frustum.intersects_aabb(&unit));
+  assert!(!frustum.intersects_aabb(&unit.transform(&Matrix4::translation(0.0, 0.0, 20.0))));
+  assert!(!frustum.intersects_aabb(&unit.transform(&Matrix4::translation(50.0, 0.0, 0.0))));
+  assert!(!frustum.intersects_aabb(&unit.transform(&Matrix4::translation(0.0, 0.0, -200.0))));
+
+  assert!(frustum.intersects_sphere(&BoundingSphere::new(vec3!(0.0, 0.0, 0.0), 1.0)));
+  assert!(!frustum.intersects_sphere(&BoundingSphere::new(vec3!(0.0, 50.0, 0.0), 1.0)));
+
+  let infinite = Frustum::from(Matrix4::perspective_infinite(1.0, 1.0, 0.1));
+  assert!(infinite.contains_point(vec3!(0.0, 0.0, -1.0e6)));
+}
+
+#[test]
+fn test_aabb() {
+  let b = Aabb::from_points(vec![vec3!(1.0, 0.0, 0.0), vec3!(-1.0, 2.0, 0.0), vec3!(0.0, 0.0, 3.0)]).unwrap();
+  assert_eq!(b, Aabb::new(vec3!(-1.0, 0.0, 0.0), vec3!(1.0, 2.0, 3.0)));
+  assert!(b.contains_point(vec3!(0.0, 1.0, 1.0)));
+  assert!(b.intersects_sphere(&BoundingSphere::new(vec3!(2.0, 1.0, 1.0), 1.0)));
+  assert!(!b.intersects(&Aabb::new(vec3!(2.0, 0.0, 0.0), vec3!(3.0, 1.0, 1.0))));
+  assert_eq!(Aabb::from_points(Vec::new()), None);
+}
+
+#[test]
+fn test_unproject() {
+  let view = Matrix4::look_at(vec3!(0.0, 0.0, 10.0), vec3!(0.0, 0.0, 0.0), vec3!(0.0, 1.0, 0.0));
+  let viewport = size!(640, 480);
+  let projections = [
+    Matrix4::perspective_fov(1.0, 640.0 / 480.0, 0.1, 100.0),
+    Matrix4::perspective_reverse_z(1.0, 640.0 / 480.0, 0.1, 100.0),
+    Matrix4::perspective_infinite_reverse_z(1.0, 640.0 / 480.0, 0.1),
+  ];
+  for projection in projections {
+    let pv = projection * view;
+    let ray = pv.unproject(vec2!(320.0, 240.0), viewport).unwrap();
+    assert_approx_eq!(ray.direction, vec3!(0.0, 0.0, -1.0));
+    assert!((ray.origin.z - 9.9).abs() < 1e-3);
+
+    // A point projected to the screen lies on the ray through that pixel.
+    let target = vec3!(1.0, 2.0, -3.0);
+    let vec3!(x, y, _z) = pv * target;
+    let pixel = vec2!((x + 1.0) * 320.0, (1.0 - y) * 240.0);
+    let ray = pv.unproject(pixel, viewport).unwrap();
+    let to_target = target - ray.origin;
+    assert_approx_eq!(ray.direction, to_target.normalize());
+  }
+}
+
+#[test]
+fn test_ray_intersections() {
+  let ray = Ray::new(vec3!(0.0, 0.0, 10.0), vec3!(0.0, 0.0, -1.0));
+
+  let plane = Plane::from_point_normal(vec3!(0.0, 0.0, 1.0), vec3!(0.0, 0.0, 1.0));
+  assert_eq!(ray.intersect_plane(&plane), Some(9.0));
+
+  let aabb = Aabb::new(vec3!(-1.0, -1.0, -1.0), vec3!(1.0, 1.0, 1.0));
+  assert_eq!(ray.intersect_aabb(&aabb), Some(9.0));
+  assert_eq!(Ray::new(vec3!(5.0, 0.0, 10.0), vec3!(0.0, 0.0, -1.0)).intersect_aabb(&aabb), None);
+
+  let sphere = BoundingSphere::new(vec3!(0.0, 0.0, 0.0), 2.0);
+  assert_eq!(ray.intersect_sphere(&sphere), Some(8.0));
+  assert_eq!(Ray::new(vec3!(0.0, 0.0, 10.0), vec3!(0.0, 0.0, 1.0)).intersect_sphere(&sphere), None);
+
+  let hit = ray.intersect_triangle(vec3!(0.0, 0.0, 0.0), vec3!(2.0, 0.0, 0.0), vec3!(0.0, 2.0, 0.0));
+  assert_eq!(hit, Some(TriangleHit { distance: 10.0, barycentric: vec3!(1.0, 0.0, 0.0) }));
+  let hit = Ray::new(vec3!(0.5, 0.5, 1.0), vec3!(0.0, 0.0, -1.0))
+    .intersect_triangle(vec3!(0.0, 0.0, 0.0), vec3!(2.0, 0.0, 0.0), vec3!(0.0, 2.0, 0.0))
+    .unwrap();
+  assert_approx_eq!(hit.barycentric, vec3!(0.5, 0.25, 0.25));
+  assert_eq!(ray.intersect_triangle(vec3!(1.0, 1.0, 0.0), vec3!(2.0, 1.0, 0.0), vec3!(1.0, 2.0, 0.0)), None);
+}
+
+#[test]
+fn test_rectangle() {
+  let a = rect!(0, 0, 10, 10);
+  let b = rect!(5, 5, 10, 10);
+  assert_eq!(a.intersection(&b), Some(rect!(5, 5, 5, 5)));
+  assert_eq!(a.intersection(&rect!(10, 0, 5, 5)), None);
+  assert_eq!(a.union(&b), rect!(0, 0, 15, 15));
+  assert!(a.contains((0, 9)));
+  assert!(!a.contains((10, 5)));
+  assert_eq!(a.inflate(1, 2), rect!(-1, -2, 12, 14));
+  assert_eq!(a.offset(3, 4), rect!(3, 4, 10, 10));
+  assert_eq!(a.split_horizontally(4), (rect!(0, 0, 4, 10), rect!(4, 0, 6, 10)));
+  assert_eq!(a.split_vertically(20), (a, rect!(0, 10, 10, 0)));
+}
+
+#[test]
+fn test_rectf() {
+  let r = RectF::from(rect!(1, 2, 3, 4));
+  assert_eq!(r, rectf!(1.0, 2.0, 3.0, 4.0));
+  assert_eq!(r.round(), rect!(1, 2, 3, 4));
+  assert!(r.contains(vec2!(1.5, 5.9)));
+
+  let r = rectf!(0.5, 0.25, 2.0, 1.5);
+  assert_eq!(r.round_out(), rect!(0, 0, 3, 2));
+  assert_eq!(r.intersection(&rectf!(1.5, 0.0, 4.0, 1.0)), Some(rectf!(1.5, 0.25, 1.0, 0.75)));
+}
+
+#[test]
+fn test_color_parsing() {
+  assert_eq!(Color::from_hex("#ff8000").unwrap(), Color::from_rgba8(255, 128, 0, 255));
+  assert_eq!(Color::from_hex("f80c").unwrap(), Color::from_rgba8(255, 136, 0, 204));
+  assert!(Color::from_hex("#ff80").is_ok());
+  assert!(Color::from_hex("#ff800").is_err());
+  assert!(Color::from_hex("#gg8000").is_err());
+  assert_eq!("RebeccaPurple".parse::<Color>().unwrap(), Color::from_rgba8(0x66, 0x33, 0x99, 255));
+  assert_eq!("transparent".parse::<Color>().unwrap(), Color::TRANSPARENT);
+  assert!("notacolor".parse::<Color>().is_err());
+  assert_eq!(Color::from_hex("#12345678").unwrap().to_rgba8(), (0x12, 0x34, 0x56, 0x78));
+}
+
+#[test]
+fn test_color_conversions() {
+  let c = Color::from_rgba8(200, 100, 50, 255);
+  let back = c.srgb_to_linear().linear_to_srgb();
+  assert_approx_eq!(Vector4::from(back), Vector4::from(c));
+
+  let (h, s, v) = c.to_hsv();
+  assert_approx_eq!(Vector4::from(Color::from_hsv(h, s, v, 1.0)), Vector4::from(c));
+  let (h, s, l) = c.to_hsl();
+  assert_approx_eq!(Vector4::from(Color::from_hsl(h, s, l, 1.0)), Vector4::from(c));
+  assert_eq!(Color::from_hsv(120.0, 1.0, 1.0, 1.0), Color::new(0.0, 1.0, 0.0, 1.0));
+
+  let translucent = Color::new(0.5, 1.0, 0.25, 0.5);
+  assert_eq!(translucent.premultiply(), Color::new(0.25, 0.5, 0.125, 0.5));
+  assert_eq!(translucent.premultiply().unpremultiply(), translucent);
+}
+
+#[test]
+fn test_easing_end_points() {
+  use crate::math::curve::Easing::*;
+  let easings = [
+    Linear, QuadIn, QuadOut, QuadInOut, CubicIn, CubicOut, CubicInOut,
+    QuartIn, QuartOut, QuartInOut, QuintIn, QuintOut, QuintInOut,
+    SineIn, SineOut, SineInOut, ExpoIn, ExpoOut, ExpoInOut, CircIn, CircOut, CircInOut,
+    BackIn, BackOut, BackInOut, ElasticIn, ElasticOut, ElasticInOut,
+    BounceIn, BounceOut, BounceInOut,
+  ];
+  for easing in easings {
+    assert!(easing.ease(0.0).abs() < 1e-3, "{:?}", easing);
+    assert!((easing.ease(1.0) - 1.0).abs() < 1e-3, "{:?}", easing);
+  }
+  assert_eq!(QuadInOut.ease(0.5), 0.5);
+  assert_eq!(curve::smoothstep(0.0, 2.0, 1.0), 0.5);
+}
+
+#[test]
+fn test_curves() {
+  let bezier = CubicBezier { p0: vec2!(0.0, 0.0), p1: vec2!(0.0, 1.0), p2: vec2!(1.0, 1.0), p3: vec2!(1.0, 0.0) };
+  assert_eq!(bezier.evaluate(0.5), vec2!(0.5, 0.75));
+
+  let hermite = Hermite { p0: vec3!(0.0, 0.0, 0.0), m0: vec3!(3.0, 0.0, 0.0), p1: vec3!(3.0, 0.0, 0.0), m1: vec3!(3.0, 0.0, 0.0) };
+  assert_approx_eq!(hermite.evaluate(1.0 / 3.0), vec3!(1.0, 0.0, 0.0));
+
+  // Catmull-Rom passes through the inner points.
+  let points = vec![vec3!(-1.0, 0.0, 0.0), vec3!(0.0, 0.0, 0.0), vec3!(1.0, 1.0, 0.0), vec3!(2.0, 0.0, 0.0), vec3!(3.0, 0.0, 0.0)];
+  let spline = CatmullRom::new(points.clone()).unwrap();
+  assert_eq!(spline.points(), &points[..]);
+  assert_approx_eq!(spline.evaluate(0.0), vec3!(0.0, 0.0, 0.0));
+  assert_approx_eq!(spline.evaluate(0.5), vec3!(1.0, 1.0, 0.0));
+  assert_approx_eq!(spline.evaluate(1.0), vec3!(2.0, 0.0, 0.0));
+
+  let rotations = CatmullRom::new((0..4).map(|i| Quaternion::yaw_rotation(i as f32 * 0.5)).collect()).unwrap();
+  let (_, angle) = rotations.evaluate(0.5).to_axis_angle();
+  assert!((angle - 0.75).abs() < 1e-4);
+
+  assert_eq!(
+    CatmullRom::new(vec![0.0f32, 1.0, 2.0]).unwrap_err().to_string(),
+    "CatmullRom needs at least four points, got 3");
+}
+
+#[test]
+fn test_arc_length() {
+  let line = CubicBezier { p0: 0.0f32, p1: 0.0, p2: 0.0, p3: 8.0 };
+  let arc = ArcLength::new(&line, 256);
+  assert!((arc.total_length() - 8.0).abs() < 1e-4);
+  let t = arc.parameter(4.0);
+  assert!((line.evaluate(t) - 4.0).abs() < 1e-2);
+  assert_eq!(arc.parameter_at_fraction(1.0), 1.0);
+}
+
+#[test]
+fn test_approx_eq() {
+  assert!(1.0f32.approx_eq(&(1.0 + 1e-6)));
+  assert!(!1.0f32.approx_eq(&1.001));
+  // Relative epsilon for large values, absolute epsilon near zero.
+  assert!(5000.0f32.approx_eq(&5000.01));
+  assert!(1e-7f32.approx_eq(&-1e-7));
+  assert!(!5000.0f32.approx_eq_eps(&5000.01, 1e-5, 0.0));
+  assert!(vec3!(1.0, 2.0, 3.0).approx_eq(&vec3!(1.0, 2.0, 3.000001)));
+  assert!(!vec4!(1.0, 2.0, 3.0, 4.0).approx_eq(&vec4!(1.0, 2.0, 3.0, 4.1)));
+  // q and -q are the same rotation.
+  let q = Quaternion::from_euler(0.1, 0.2, 0.3);
+  assert!(q.approx_eq(&-q));
+}
+
+// Deterministic xorshift generator for the property tests below.
+struct Rng(u32);
+
+impl Rng {
+  fn next(&mut self) -> u32 {
+    self.0 ^= self.0 << 13;
+    self.0 ^= self.0 >> 17;
+    self.0 ^= self.0 << 5;
+    self.0
+  }
+
+  fn range(&mut self, min: f32, max: f32) -> f32 {
+    min + (max - min) * (self.next() as f32 / u32::MAX as f32)
+  }
+
+  fn vector3(&mut self, min: f32, max: f32) -> Vector3 {
+    vec3!(self.range(min, max), self.range(min, max), self.range(min, max))
+  }
+
+  fn quaternion(&mut self) -> Quaternion {
+    let axis = self.vector3(-1.0, 1.0) + vec3!(0.0, 0.0, 0.01);
+    Quaternion::from_axis_angle(axis, self.range(-3.0, 3.0))
+  }
+
+  fn affine(&mut self) -> Matrix4 {
+    let scale = vec3!(self.range(0.5, 4.0), self.range(0.5, 4.0), self.range(0.5, 4.0));
+    Matrix4::compose(self.vector3(-100.0, 100.0), self.quaternion(), scale)
+  }
+}
+
+const PROPERTY_CASES: usize = 200;
+
+#[test]
+fn test_property_look_at_is_orthonormal() {
+  let mut rng = Rng(1);
+  for _ in 0..PROPERTY_CASES {
+    let eye = rng.vector3(-50.0, 50.0);
+    let target = rng.vector3(-50.0, 50.0);
+    let view = Matrix4::look_at(eye, target, vec3!(0.0, 1.0, 0.0));
+    let rotation = Matrix3::from(view);
+
+    assert!((rotation * rotation.transpose()).approx_eq_eps(&Matrix3::IDENT, 1e-4, 1e-4));
+    assert!(rotation.determinant().approx_eq_eps(&1.0, 1e-4, 1e-4));
+    // The camera sits at the origin and looks down -Z.
+    assert!((view * eye).approx_eq_eps(&vec3!(0.0, 0.0, 0.0), 1e-3, 1e-4));
+    let distance = (target - eye).norm();
+    assert!((view * target).approx_eq_eps(&vec3!(0.0, 0.0, -distance), 1e-3, 1e-4));
+  }
+}
+
+#[test]
+fn test_property_perspective_maps_near_and_far() {
+  let mut rng = Rng(2);
+  for _ in 0..PROPERTY_CASES {
+    let fov_y = rng.range(0.2, 2.5);
+    let aspect_ratio = rng.range(0.5, 2.5);
+    let near = rng.range(0.01, 1.0);
+    let far = near + rng.range(1.0, 1000.0);
+
+    let m = Matrix4::perspective_fov(fov_y, aspect_ratio, near, far);
+    assert!(project_depth(m, -near).approx_eq_eps(&-1.0, 1e-4, 1e-4));
+    assert!(project_depth(m, -far).approx_eq_eps(&1.0, 1e-4, 1e-4));
+
+    // The top edge of the field of view maps to y = 1.
+    let top = near * (fov_y / 2.0).tan();
+    assert!((m * vec3!(0.0, top, -near)).y.approx_eq_eps(&1.0, 1e-4, 1e-4));
+  }
+}
+
+#[test]
+fn test_property_inverse_round_trip() {
+  let mut rng = Rng(3);
+  for _ in 0..PROPERTY_CASES {
+    let m = rng.affine();
+    let inverse = m.inverse().unwrap();
+    assert!((m * inverse).approx_eq_eps(&Matrix4::IDENT, 1e-4, 1e-4));
+    assert!((inverse * m).approx_eq_eps(&Matrix4::IDENT, 1e-4, 1e-4));
+    assert!(m.inverse_affine().unwrap().approx_eq_eps(&inverse, 1e-4, 1e-4));
+
+    let p = rng.vector3(-10.0, 10.0);
+    assert!((inverse * (m * p)).approx_eq_eps(&p, 1e-3, 1e-4));
+
+    let (t, r, s) = m.decompose();
+    assert!(Matrix4::compose(t, r, s).approx_eq_eps(&m, 1e-4, 1e-4));
+  }
+}
+
+#[test]
+fn test_property_rotation_composition() {
+  let mut rng = Rng(4);
+  for _ in 0..PROPERTY_CASES {
+    let a = rng.quaternion();
+    let b = rng.quaternion();
+    let v = rng.vector3(-10.0, 10.0);
+
+    assert_approx_eq!(Matrix4::from(a * b), Matrix4::from(a) * Matrix4::from(b));
+    assert!(((a * b) * v).approx_eq_eps(&(a * (b * v)), 1e-4, 1e-4));
+    assert!((a * v).norm().approx_eq_eps(&v.norm(), 1e-4, 1e-4));
+    assert_approx_eq!(Quaternion::from(Matrix4::from(a)), a);
+    assert_approx_eq!(a * a.inverse(), Quaternion::IDENT);
+
+    let (pitch, yaw, roll) = (rng.range(-3.0, 3.0), rng.range(-3.0, 3.0), rng.range(-3.0, 3.0));
+    assert_approx_eq!(Matrix4::from(Quaternion::from_euler(pitch, yaw, roll)), Matrix4::rotation(pitch, yaw, roll));
+  }
+}
@@ -2,7 +2,23 @@ use std::ops::{Add, Div, Mul, Neg, Sub};
 
 use crate::{vec2, vec3, vec4};
 
+// Generates swizzle accessors, e.g. `zyx -> Vector3 = vec3!(z, y, x)`
+// expands to `pub fn zyx(self) -> Vector3`.
+macro_rules! swizzle {
+    ($t:ty { $($name:ident -> $out:ty = $mac:ident!($($c:ident),+)),* $(,)? }) => {
+        #[allow(dead_code)]
+        impl $t {
+            $(
+                pub fn $name(self) -> $out {
+                    $mac!($(self.$c),+)
+                }
+            )*
+        }
+    };
+}
+
 #[derive(Debug, Copy, Clone, PartialEq)]
+#[repr(C)]
 pub struct Vector2 {
     pub x: f32,
     pub y: f32,
@@ -41,6 +57,19 @@ impl From<Vector3> for Vector4 {
     }
 }
 
+impl From<Vector4> for Vector3 {
+    fn from(value: Vector4) -> Self {
+        value.xyz()
+    }
+}
+
+impl From<(f32, f32)> for Vector2 {
+    fn from(value: (f32, f32)) -> Self {
+        let (x, y) = value;
+        vec2!(x, y)
+    }
+}
+
 impl From<(f32, f32, f32)> for Vector3 {
     fn from(value: (f32, f32, f32)) -> Self {
         match value {
@@ -57,6 +86,49 @@ impl From<(f32, f32, f32, f32)> for Vector4 {
     }
 }
 
+#[allow(dead_code)]
+impl Vector2 {
+    pub fn norm(self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    pub fn normalize(self) -> Vector2 {
+        self / self.norm()
+    }
+
+    pub fn dot(self, rhs: Vector2) -> f32 {
+        let vec2!(ax, ay) = self;
+        let vec2!(bx, by) = rhs;
+        ax * bx + ay * by
+    }
+
+    // Same as norm.
+    pub fn length(self) -> f32 {
+        self.norm()
+    }
+
+    pub fn lerp(self, rhs: Vector2, t: f32) -> Vector2 {
+        self + (rhs - self) * t
+    }
+
+    pub fn min(self, rhs: Vector2) -> Vector2 {
+        let vec2!(ax, ay) = self;
+        let vec2!(bx, by) = rhs;
+        vec2!(ax.min(bx), ay.min(by))
+    }
+
+    pub fn max(self, rhs: Vector2) -> Vector2 {
+        let vec2!(ax, ay) = self;
+        let vec2!(bx, by) = rhs;
+        vec2!(ax.max(bx), ay.max(by))
+    }
+
+    pub fn clamp(self, min: Vector2, max: Vector2) -> Vector2 {
+        self.max(min).min(max)
+    }
+}
+
+#[allow(dead_code)]
 impl Vector3 {
     pub fn norm(self) -> f32 {
         self.dot(self).sqrt()
@@ -79,6 +151,125 @@ impl Vector3 {
             }
         }
     }
+
+    // Same as norm.
+    pub fn length(self) -> f32 {
+        self.norm()
+    }
+
+    pub fn lerp(self, rhs: Vector3, t: f32) -> Vector3 {
+        self + (rhs - self) * t
+    }
+
+    pub fn min(self, rhs: Vector3) -> Vector3 {
+        let vec3!(ax, ay, az) = self;
+        let vec3!(bx, by, bz) = rhs;
+        vec3!(ax.min(bx), ay.min(by), az.min(bz))
+    }
+
+    pub fn max(self, rhs: Vector3) -> Vector3 {
+        let vec3!(ax, ay, az) = self;
+        let vec3!(bx, by, bz) = rhs;
+        vec3!(ax.max(bx), ay.max(by), az.max(bz))
+    }
+
+    pub fn clamp(self, min: Vector3, max: Vector3) -> Vector3 {
+        self.max(min).min(max)
+    }
+}
+
+#[allow(dead_code)]
+impl Vector4 {
+    pub fn norm(self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    pub fn normalize(self) -> Vector4 {
+        self / self.norm()
+    }
+
+    pub fn dot(self, rhs: Vector4) -> f32 {
+        let vec4!(ax, ay, az, aw) = self;
+        let vec4!(bx, by, bz, bw) = rhs;
+        ax * bx + ay * by + az * bz + aw * bw
+    }
+
+    // Same as norm.
+    pub fn length(self) -> f32 {
+        self.norm()
+    }
+
+    pub fn lerp(self, rhs: Vector4, t: f32) -> Vector4 {
+        self + (rhs - self) * t
+    }
+
+    pub fn min(self, rhs: Vector4) -> Vector4 {
+        let vec4!(ax, ay, az, aw) = self;
+        let vec4!(bx, by, bz, bw) = rhs;
+        vec4!(ax.min(bx), ay.min(by), az.min(bz), aw.min(bw))
+    }
+
+    pub fn max(self, rhs: Vector4) -> Vector4 {
+        let vec4!(ax, ay, az, aw) = self;
+        let vec4!(bx, by, bz, bw) = rhs;
+        vec4!(ax.max(bx), ay.max(by), az.max(bz), aw.max(bw))
+    }
+
+    pub fn clamp(self, min: Vector4, max: Vector4) -> Vector4 {
+        self.max(min).min(max)
+    }
+}
+
+impl Add<Vector2> for Vector2 {
+    type Output = Vector2;
+    fn add(self, rhs: Vector2) -> Self::Output {
+        let vec2!(ax, ay) = self;
+        let vec2!(bx, by) = rhs;
+        vec2!(ax + bx, ay + by)
+    }
+}
+
+impl Sub<Vector2> for Vector2 {
+    type Output = Vector2;
+    fn sub(self, rhs: Vector2) -> Self::Output {
+        let vec2!(ax, ay) = self;
+        let vec2!(bx, by) = rhs;
+        vec2!(ax - bx, ay - by)
+    }
+}
+
+impl Mul<f32> for Vector2 {
+    type Output = Vector2;
+    fn mul(self, rhs: f32) -> Self::Output {
+        let vec2!(x, y) = self;
+        vec2!(x * rhs, y * rhs)
+    }
+}
+
+// Component-wise multiplication.
+impl Mul<Vector2> for Vector2 {
+    type Output = Vector2;
+    fn mul(self, rhs: Vector2) -> Self::Output {
+        let vec2!(ax, ay) = self;
+        let vec2!(bx, by) = rhs;
+        vec2!(ax * bx, ay * by)
+    }
+}
+
+impl Div<f32> for Vector2 {
+    type Output = Vector2;
+    fn div(self, rhs: f32) -> Self::Output {
+        let vec2!(x, y) = self;
+        vec2!(x / rhs, y / rhs)
+    }
+}
+
+impl Neg for Vector2 {
+    type Output = Vector2;
+    fn neg(self) -> Self::Output {
+        let vec2!(x, y) = self;
+        vec2!(-x, -y)
+    }
 }
 
 impl Add<Vector3> for Vector3 {
@@ -131,6 +322,88 @@ impl Neg for Vector3 {
     }
 }
 
+// Component-wise multiplication.
+impl Mul<Vector3> for Vector3 {
+    type Output = Vector3;
+    fn mul(self, rhs: Vector3) -> Self::Output {
+        let vec3!(ax, ay, az) = self;
+        let vec3!(bx, by, bz) = rhs;
+        vec3!(ax * bx, ay * by, az * bz)
+    }
+}
+
+impl Add<Vector4> for Vector4 {
+    type Output = Vector4;
+    fn add(self, rhs: Vector4) -> Self::Output {
+        let vec4!(ax, ay, az, aw) = self;
+        let vec4!(bx, by, bz, bw) = rhs;
+        vec4!(ax + bx, ay + by, az + bz, aw + bw)
+    }
+}
+
+impl Sub<Vector4> for Vector4 {
+    type Output = Vector4;
+    fn sub(self, rhs: Vector4) -> Self::Output {
+        let vec4!(ax, ay, az, aw) = self;
+        let vec4!(bx, by, bz, bw) = rhs;
+        vec4!(ax - bx, ay - by, az - bz, aw - bw)
+    }
+}
+
+impl Mul<f32> for Vector4 {
+    type Output = Vector4;
+    fn mul(self, rhs: f32) -> Self::Output {
+        let vec4!(x, y, z, w) = self;
+        vec4!(x * rhs, y * rhs, z * rhs, w * rhs)
+    }
+}
+
+// Component-wise multiplication.
+impl Mul<Vector4> for Vector4 {
+    type Output = Vector4;
+    fn mul(self, rhs: Vector4) -> Self::Output {
+        let vec4!(ax, ay, az, aw) = self;
+        let vec4!(bx, by, bz, bw) = rhs;
+        vec4!(ax * bx, ay * by, az * bz, aw * bw)
+    }
+}
+
+impl Div<f32> for Vector4 {
+    type Output = Vector4;
+    fn div(self, rhs: f32) -> Self::Output {
+        let vec4!(x, y, z, w) = self;
+        vec4!(x / rhs, y / rhs, z / rhs, w / rhs)
+    }
+}
+
+impl Neg for Vector4 {
+    type Output = Vector4;
+    fn neg(self) -> Self::Output {
+        let vec4!(x, y, z, w) = self;
+        vec4!(-x, -y, -z, -w)
+    }
+}
+
+swizzle!(Vector2 {
+    yx -> Vector2 = vec2!(y, x),
+});
+
+swizzle!(Vector3 {
+    xy -> Vector2 = vec2!(x, y),
+    xz -> Vector2 = vec2!(x, z),
+    yz -> Vector2 = vec2!(y, z),
+    yx -> Vector2 = vec2!(y, x),
+    zyx -> Vector3 = vec3!(z, y, x),
+});
+
+swizzle!(Vector4 {
+    xy -> Vector2 = vec2!(x, y),
+    zw -> Vector2 = vec2!(z, w),
+    xyz -> Vector3 = vec3!(x, y, z),
+    zyx -> Vector3 = vec3!(z, y, x),
+    wzyx -> Vector4 = vec4!(w, z, y, x),
+});
+
 mod macros {
     #[macro_export]
 macro_rules! vec2 {